@@ -24,10 +24,14 @@
 
 use embedded_io::ReadReady;
 use embedded_io_async::{Read, Write};
-use heapless::Vec;
+use heapless::{String, Vec};
 use rand_core::RngCore;
 
 use crate::client::client_config::ClientConfig;
+#[cfg(feature = "metrics")]
+use crate::network::stats::ClientStats;
+#[cfg(feature = "wire-hook")]
+use crate::network::WireHook;
 use crate::packet::v5::publish_packet::QualityOfService::{self, QoS1};
 use crate::packet::v5::reason_codes::ReasonCode;
 
@@ -65,6 +69,16 @@ where
         }
     }
 
+    /// Method prepares the client for connecting again over the new network connection
+    /// `network_driver`, without constructing a new client. Call `disconnect` first to close
+    /// the previous connection gracefully, otherwise it is dropped without DISCONNECT.
+    /// The buffers, the `ClientConfig` (including the rng) and the client identifier assigned
+    /// by the broker survive. Keep alive, maximum QoS and features available on the broker are
+    /// replaced by the values from the next CONNACK. Call `connect_to_broker` afterwards.
+    pub fn reset_for_reconnect(&mut self, network_driver: T) {
+        self.raw.set_network(network_driver);
+    }
+
     /// Method allows client connect to server. Client is connecting to the specified broker
     /// in the `ClientConfig`. Method selects proper implementation of the MQTT version based on the config.
     /// If the connection to the broker fails, method returns Err variable that contains
@@ -80,6 +94,79 @@ where
         }
     }
 
+    /// Method returns the client identifier of the connection. If the `ClientConfig` contains
+    /// an empty client identifier, the identifier assigned by the broker during the last
+    /// successful connect is returned. MQTTv5 client presents this identifier automatically
+    /// when it connects again, so the session can be resumed.
+    pub fn client_identifier(&self) -> &str {
+        self.raw.client_identifier()
+    }
+
+    /// Method returns the response information sent by the broker in the CONNACK. Broker sends
+    /// it only if it was requested with `ClientConfig::add_request_response_information`.
+    /// Response information longer than `MAX_RESPONSE_INFORMATION_LEN` is not kept.
+    pub fn response_information(&self) -> Option<&str> {
+        self.raw.response_information()
+    }
+
+    /// Method builds the response topic for the request with the `correlation` under the
+    /// response information sent by the broker. The topic can be subscribed and used as
+    /// the Response Topic property of the request. Returns `None` if the broker did not send
+    /// the response information or the topic does not fit into `N` bytes.
+    pub fn response_topic_for<const N: usize>(&self, correlation: &str) -> Option<String<N>> {
+        let base = self.response_information()?;
+        let mut topic = String::new();
+        topic.push_str(base).ok()?;
+        if !base.ends_with('/') {
+            topic.push('/').ok()?;
+        }
+        topic.push_str(correlation).ok()?;
+        Some(topic)
+    }
+
+    /// Method returns the keep alive interval in seconds used for the connection. Broker can
+    /// override the interval from the `ClientConfig` in the CONNACK. Value 0 means that the
+    /// keep alive is disabled and `send_ping` does not have to be called.
+    pub fn keep_alive(&self) -> u16 {
+        self.raw.keep_alive()
+    }
+
+    /// Method returns the session expiry interval in seconds used for the connection. It is
+    /// the value requested with the `SessionExpiryInterval` property of the `ClientConfig`,
+    /// unless the broker replaced it in the CONNACK. Value 0 means the session ends with
+    /// the network connection.
+    pub fn session_expiry_interval(&self) -> u32 {
+        self.raw.session_expiry_interval()
+    }
+
+    /// Method returns the highest QoS which can be used for the connection. It respects
+    /// the Maximum QoS sent by the broker in the CONNACK and is never higher than QoS 1.
+    pub fn maximum_qos(&self) -> QualityOfService {
+        self.raw.maximum_qos()
+    }
+
+    /// Method returns the packet and byte counters of the current network connection.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> ClientStats {
+        self.raw.stats()
+    }
+
+    /// Method sets the hook which is called with the bytes of every complete packet
+    /// sent or received by the client. Useful for debugging interoperability issues.
+    /// The hook is a closure borrowed by the client, so it can collect the packets. The hook
+    /// does not have to be `Send`, so with the `wire-hook` feature the client is not `Send`.
+    #[cfg(feature = "wire-hook")]
+    pub fn set_wire_hook(&mut self, hook: Option<WireHook<'a>>) {
+        self.raw.set_wire_hook(hook);
+    }
+
+    /// Method returns true if the client is connected to the broker. Connection is
+    /// considered closed after `disconnect`, after the broker sends DISCONNECT or when
+    /// the network connection fails while waiting for a packet.
+    pub fn is_connected(&self) -> bool {
+        self.raw.is_connected()
+    }
+
     /// Method allows client disconnect from the server. Client disconnects from the specified broker
     /// in the `ClientConfig`. Method selects proper implementation of the MQTT version based on the config.
     /// If the disconnect from the broker fails, method returns Err variable that contains
@@ -89,10 +176,39 @@ where
         Ok(())
     }
 
+    /// Method returns the reason why the last connection was closed: the reason code of the
+    /// DISCONNECT sent by the client or by the broker, or `NetworkError` if the network failed.
+    /// Returns `None` while the connection made by the last `connect_to_broker` is open.
+    pub fn last_close_reason(&self) -> Option<ReasonCode> {
+        self.raw.last_close_reason()
+    }
+
+    /// Method returns the flags of the fixed header of the last PUBLISH received by
+    /// `receive_message`, the lower four bits with DUP, QoS and RETAIN, unchanged. It is
+    /// meant for tools that need byte-exact fidelity, e.g. proxies. Returns `None` if no
+    /// message was received yet.
+    pub fn last_publish_flags(&self) -> Option<u8> {
+        self.raw.last_publish_flags()
+    }
+
+    /// Method disconnects from the broker like `disconnect`, but tells the broker the `reason`
+    /// of the disconnect, e.g. `AdministrativeAction`. Reason has to be one the client is allowed
+    /// to send (see `ReasonCode::allowed_in_client_disconnect`), otherwise `InvalidArgument`
+    /// is returned and nothing is sent. MQTTv3 DISCONNECT has no reason code, so only `Success`
+    /// is accepted, other reasons fail with `UnsupportedProtocolVersion` and nothing is sent.
+    pub async fn disconnect_with_reason<'b>(
+        &'b mut self,
+        reason: ReasonCode,
+    ) -> Result<(), ReasonCode> {
+        self.raw.disconnect_with_reason(reason).await
+    }
+
     /// Method allows sending message to broker specified from the ClientConfig. Client sends the
     /// message from the parameter `message` to the topic `topic_name` on the broker
     /// specified in the ClientConfig. If the send fails method returns Err with reason code
-    /// received by broker.
+    /// received by broker. QoS 1 message is published when the broker answers PUBACK with
+    /// a reason code below 0x80, e.g. `NoMatchingSubscribers`, which is only logged.
+    /// Use `send_message_ack_reason` to get the reason code.
     pub async fn send_message<'b>(
         &'b mut self,
         topic_name: &'b str,
@@ -100,17 +216,82 @@ where
         qos: QualityOfService,
         retain: bool,
     ) -> Result<(), ReasonCode> {
+        self.send_message_ack_reason(topic_name, message, qos, retain)
+            .await?;
+        Ok(())
+    }
+
+    /// Method sends the message like `send_message` and returns the reason code of the
+    /// PUBACK. It can be `NoMatchingSubscribers`, so the caller can warn about it, but the
+    /// message must not be sent again. QoS 0 message returns `Success`.
+    pub async fn send_message_ack_reason<'b>(
+        &'b mut self,
+        topic_name: &'b str,
+        message: &'b [u8],
+        qos: QualityOfService,
+        retain: bool,
+    ) -> Result<ReasonCode, ReasonCode> {
         let identifier = self
             .raw
             .send_message(topic_name, message, qos, retain)
             .await?;
+        self.wait_for_puback(qos, identifier).await
+    }
+
+    /// Method returns the number of bytes of the PUBLISH packet sent by `send_message` for the
+    /// topic `topic_name` and a message with `message_len` bytes, so it can be checked against
+    /// the send buffer or the Maximum Packet Size of the broker before publishing.
+    pub fn publish_size(
+        &self,
+        topic_name: &str,
+        message_len: usize,
+        qos: QualityOfService,
+    ) -> Result<usize, ReasonCode> {
+        self.raw.publish_size(topic_name, message_len, qos)
+    }
+
+    /// Method disconnects from the broker and asks it to publish the will message set by
+    /// `ClientConfig::add_will`. `disconnect` closes the connection normally and the broker
+    /// discards the will. MQTTv3 brokers discard the will on every DISCONNECT, so the method
+    /// fails with `UnsupportedProtocolVersion` and keeps the connection open.
+    pub async fn disconnect_with_will<'b>(&'b mut self) -> Result<(), ReasonCode> {
+        self.raw
+            .disconnect_with_reason(ReasonCode::DisconnectWithWillMessage)
+            .await
+    }
+
+    /// Method sends the message like `send_message`, but with the packet `identifier` chosen
+    /// by the caller, e.g. a bridge keeping the identifier assigned by the upstream broker.
+    /// Identifier 0 is not valid and is rejected with `ProtocolError`.
+    pub async fn send_message_with_identifier<'b>(
+        &'b mut self,
+        topic_name: &'b str,
+        message: &'b [u8],
+        qos: QualityOfService,
+        retain: bool,
+        identifier: u16,
+    ) -> Result<(), ReasonCode> {
+        self.raw
+            .send_message_with_identifier(topic_name, message, qos, retain, identifier)
+            .await?;
+        self.wait_for_puback(qos, identifier).await?;
+        Ok(())
+    }
 
+    async fn wait_for_puback(
+        &mut self,
+        qos: QualityOfService,
+        identifier: u16,
+    ) -> Result<ReasonCode, ReasonCode> {
         // QoS1
         if qos == QoS1 {
             match self.raw.poll::<0>().await? {
-                Event::Puback(ack_identifier) => {
+                Event::Puback(ack_identifier, reason) => {
                     if identifier == ack_identifier {
-                        Ok(())
+                        if reason != ReasonCode::Success {
+                            warn!("Message was published with PUBACK reason code: {}", reason);
+                        }
+                        Ok(reason)
                     } else {
                         Err(ReasonCode::PacketIdentifierNotFound)
                     }
@@ -120,14 +301,29 @@ where
                 _ => Err(ReasonCode::ImplementationSpecificError),
             }
         } else {
-            Ok(())
+            Ok(ReasonCode::Success)
         }
     }
 
+    /// Method removes the retained message of the topic `topic_name` from the broker.
+    /// It sends an empty message with the retain flag set as defined by the MQTT protocol.
+    /// Returns the packet identifier of the PUBLISH if the `qos` is above 0, otherwise `None`.
+    pub async fn clear_retained<'b>(
+        &'b mut self,
+        topic_name: &'b str,
+        qos: QualityOfService,
+    ) -> Result<Option<u16>, ReasonCode> {
+        let identifier = self.raw.send_message(topic_name, &[], qos, true).await?;
+        self.wait_for_puback(qos, identifier).await?;
+        Ok((qos != QualityOfService::QoS0).then_some(identifier))
+    }
+
     /// Method allows client subscribe to multiple topics specified in the parameter
     /// `topic_names` on the broker specified in the `ClientConfig`. Generics `TOPICS`
     /// sets the value of the `topics_names` vector. MQTT protocol implementation
-    /// is selected automatically.
+    /// is selected automatically. Shared subscription filters (see `topic_filter::shared`)
+    /// are rejected with `SharedSubscriptionNotSupported` and filters with wildcards with
+    /// `WildcardSubscriptionNotSupported` if the broker does not support them.
     pub async fn subscribe_to_topics<'b, const TOPICS: usize>(
         &'b mut self,
         topic_names: &'b Vec<&'b str, TOPICS>,
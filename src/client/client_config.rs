@@ -27,9 +27,20 @@ use rand_core::RngCore;
 
 use crate::packet::v5::property::Property;
 use crate::packet::v5::publish_packet::QualityOfService;
-use crate::utils::types::{BinaryData, EncodedString};
+use crate::packet::v5::reason_codes::ReasonCode;
+use crate::packet::v5::subscription_packet::RetainHandling;
+use crate::utils::types::{BinaryData, EncodedString, StringPair};
+
+/// Largest subscription identifier, the maximum of the variable byte integer.
+pub const MAX_SUBSCRIPTION_IDENTIFIER: u32 = 268_435_455;
+
+/// Returns true if the `identifier` can be sent as a subscription identifier.
+pub(crate) fn is_valid_subscription_identifier(identifier: u32) -> bool {
+    (1..=MAX_SUBSCRIPTION_IDENTIFIER).contains(&identifier)
+}
 
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum MqttVersion {
     MQTTv3,
     MQTTv5,
@@ -47,6 +58,9 @@ pub enum MqttVersion {
 #[derive(Clone)]
 pub struct ClientConfig<'a, const MAX_PROPERTIES: usize, T: RngCore> {
     pub max_subscribe_qos: QualityOfService,
+    pub required_qos: QualityOfService,
+    pub retain_handling: RetainHandling,
+    pub subscription_identifier: Option<u32>,
     pub keep_alive: u16,
     pub username_flag: bool,
     pub username: EncodedString<'a>,
@@ -67,6 +81,9 @@ impl<'a, const MAX_PROPERTIES: usize, T: RngCore> ClientConfig<'a, MAX_PROPERTIE
     pub fn new(version: MqttVersion, rng: T) -> Self {
         Self {
             max_subscribe_qos: QualityOfService::QoS0,
+            required_qos: QualityOfService::QoS0,
+            retain_handling: RetainHandling::AlwaysSend,
+            subscription_identifier: None,
             keep_alive: 60,
             username_flag: false,
             username: EncodedString::new(),
@@ -88,6 +105,31 @@ impl<'a, const MAX_PROPERTIES: usize, T: RngCore> ClientConfig<'a, MAX_PROPERTIE
         self.max_subscribe_qos = qos;
     }
 
+    /// Method sets the lowest QoS the application needs. If the broker supports only a lower
+    /// QoS, connecting fails with `QoSNotSupported` right after the CONNACK is received and
+    /// the client disconnects with the same reason code.
+    pub fn add_required_qos(&mut self, qos: QualityOfService) {
+        self.required_qos = qos;
+    }
+
+    /// Method sets the retain handling used for the subscriptions. Retain handling is
+    /// not available in MQTTv3, retained messages are always sent there.
+    pub fn add_retain_handling(&mut self, retain_handling: RetainHandling) {
+        self.retain_handling = retain_handling;
+    }
+
+    /// Method sets the subscription identifier sent with the subscriptions. Broker sends
+    /// it back in the matching PUBLISH packets. Valid identifiers are 1 to 268 435 455,
+    /// other values are rejected with `InvalidArgument`. Subscription identifiers are
+    /// not available in MQTTv3, the identifier is not sent there.
+    pub fn add_subscription_identifier(&mut self, identifier: u32) -> Result<(), ReasonCode> {
+        if !is_valid_subscription_identifier(identifier) {
+            return Err(ReasonCode::InvalidArgument);
+        }
+        self.subscription_identifier = Some(identifier);
+        Ok(())
+    }
+
     pub fn add_will(&mut self, topic: &'a str, payload: &'a [u8], retain: bool) {
         let mut topic_s = EncodedString::new();
         topic_s.string = topic;
@@ -129,8 +171,34 @@ impl<'a, const MAX_PROPERTIES: usize, T: RngCore> ClientConfig<'a, MAX_PROPERTIE
         }
     }
 
-    /// Method encode the `max_packet_size` attribute as property to the properties Vec.
+    /// Method asks the broker to send the response information in the CONNACK. The broker
+    /// is not required to send it, see `MqttClient::response_information`.
+    pub fn add_request_response_information(&mut self) {
+        self.add_property(Property::RequestResponseInformation(1));
+    }
+
+    /// Method adds the user property with the `name` and `value` to the properties Vec.
+    /// User properties are sent in the CONNECT packet.
+    pub fn add_user_property(&mut self, name: &'a str, value: &'a str) {
+        let mut pair = StringPair::new();
+        pair.name.string = name;
+        pair.name.len = name.len() as u16;
+        pair.value.string = value;
+        pair.value.len = value.len() as u16;
+        self.add_property(Property::UserProperty(pair));
+    }
+
+    /// Method adds the maximum packet size property. If the property was already added
+    /// by the previous connect, its value is updated, so it is not sent twice on reconnect.
     pub fn add_max_packet_size_as_prop(&mut self) -> u32 {
+        if let Some(prop) = self
+            .properties
+            .iter_mut()
+            .find(|prop| matches!(prop, Property::MaximumPacketSize(_)))
+        {
+            *prop = Property::MaximumPacketSize(self.max_packet_size);
+            return 0;
+        }
         if self.properties.len() < MAX_PROPERTIES {
             let prop = Property::MaximumPacketSize(self.max_packet_size);
             self.properties.push(prop);
@@ -1,11 +1,12 @@
 use embedded_io::ReadReady;
 use embedded_io_async::{Read, Write};
-use heapless::Vec;
+use heapless::{String, Vec};
 use rand_core::RngCore;
 
 use crate::{
     encoding::variable_byte_integer::{VariableByteInteger, VariableByteIntegerDecoder},
     network::NetworkConnection,
+    packet::v3,
     packet::v5::{
         connack_packet::ConnackPacket,
         connect_packet::ConnectPacket,
@@ -14,6 +15,7 @@ use crate::{
         packet_type::PacketType,
         pingreq_packet::PingreqPacket,
         pingresp_packet::PingrespPacket,
+        property::Property,
         puback_packet::PubackPacket,
         publish_packet::{PublishPacket, QualityOfService},
         reason_codes::ReasonCode,
@@ -22,14 +24,25 @@ use crate::{
         unsuback_packet::UnsubackPacket,
         unsubscription_packet::UnsubscriptionPacket,
     },
-    utils::{buffer_reader::BuffReader, buffer_writer::BuffWriter, types::BufferError},
+    utils::{
+        buffer_reader::BuffReader,
+        buffer_writer::BuffWriter,
+        topic_filter,
+        types::{BufferError, EncodedString},
+    },
 };
 
-use super::client_config::{ClientConfig, MqttVersion};
+use super::client_config::{is_valid_subscription_identifier, ClientConfig, MqttVersion};
+#[cfg(feature = "metrics")]
+use crate::network::stats::ClientStats;
+#[cfg(feature = "wire-hook")]
+use crate::network::{Direction, WireHook};
 
 pub enum Event<'a> {
     Connack,
-    Puback(u16),
+    /// PUBACK with the packet identifier and the reason code, which is `Success` for MQTTv3
+    /// or a success-like code such as `NoMatchingSubscribers`.
+    Puback(u16, ReasonCode),
     Suback(u16),
     Unsuback(u16),
     Pingresp,
@@ -37,6 +50,25 @@ pub enum Event<'a> {
     Disconnect(ReasonCode),
 }
 
+// Payload of the message is not printed, only its length, so large messages
+// do not flood the log.
+#[cfg(feature = "defmt")]
+impl defmt::Format for Event<'_> {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Event::Connack => defmt::write!(f, "Connack"),
+            Event::Puback(id, reason) => defmt::write!(f, "Puback({}, {})", id, reason),
+            Event::Suback(id) => defmt::write!(f, "Suback({})", id),
+            Event::Unsuback(id) => defmt::write!(f, "Unsuback({})", id),
+            Event::Pingresp => defmt::write!(f, "Pingresp"),
+            Event::Message(topic, payload) => {
+                defmt::write!(f, "Message({}, {} bytes)", topic, payload.len())
+            }
+            Event::Disconnect(reason) => defmt::write!(f, "Disconnect({})", reason),
+        }
+    }
+}
+
 pub struct RawMqttClient<'a, T, const MAX_PROPERTIES: usize, R: RngCore>
 where
     T: Read + Write,
@@ -47,8 +79,26 @@ where
     recv_buffer: &'a mut [u8],
     recv_buffer_len: usize,
     config: ClientConfig<'a, MAX_PROPERTIES, R>,
+    connected: bool,
+    assigned_client_id: String<MAX_ASSIGNED_CLIENT_ID_LEN>,
+    response_information: String<MAX_RESPONSE_INFORMATION_LEN>,
+    shared_subscription_available: bool,
+    wildcard_subscription_available: bool,
+    subscription_identifiers_available: bool,
+    server_keep_alive: Option<u16>,
+    server_session_expiry_interval: Option<u32>,
+    server_maximum_qos: QualityOfService,
+    close_reason: Option<ReasonCode>,
+    publish_flags: Option<u8>,
+    wire_hook: WireHookSlot<'a>,
 }
 
+/// Maximal length of the client identifier assigned by the broker which is kept by the client.
+pub const MAX_ASSIGNED_CLIENT_ID_LEN: usize = 64;
+
+/// Maximal length of the response information sent by the broker which is kept by the client.
+pub const MAX_RESPONSE_INFORMATION_LEN: usize = 64;
+
 impl<'a, T, const MAX_PROPERTIES: usize, R> RawMqttClient<'a, T, MAX_PROPERTIES, R>
 where
     T: Read + Write,
@@ -69,7 +119,138 @@ where
             recv_buffer,
             recv_buffer_len,
             config,
+            connected: false,
+            assigned_client_id: String::new(),
+            response_information: String::new(),
+            shared_subscription_available: true,
+            wildcard_subscription_available: true,
+            subscription_identifiers_available: true,
+            server_keep_alive: None,
+            server_session_expiry_interval: None,
+            server_maximum_qos: QualityOfService::QoS2,
+            close_reason: None,
+            publish_flags: None,
+            wire_hook: Default::default(),
+        }
+    }
+
+    /// Returns the client identifier used for the connection. If the client connected with
+    /// an empty client identifier, identifier assigned by the broker in the CONNACK is returned.
+    pub fn client_identifier(&self) -> &str {
+        if self.config.client_id.len > 0 {
+            self.config.client_id.string
+        } else {
+            self.assigned_client_id.as_str()
+        }
+    }
+
+    /// Returns the response information sent by the broker in the CONNACK of the current
+    /// connection, if there was any.
+    pub fn response_information(&self) -> Option<&str> {
+        if self.response_information.is_empty() {
+            None
+        } else {
+            Some(self.response_information.as_str())
+        }
+    }
+
+    /// Returns the keep alive interval in seconds negotiated for the connection. If the broker
+    /// sent the Server Keep Alive in the CONNACK, it overrides the value from the `ClientConfig`.
+    /// Value 0 means the keep alive mechanism is disabled and no PINGREQ has to be sent.
+    pub fn keep_alive(&self) -> u16 {
+        self.server_keep_alive.unwrap_or(self.config.keep_alive)
+    }
+
+    /// Returns the session expiry interval in seconds negotiated for the connection. If the
+    /// broker sent the Session Expiry Interval in the CONNACK, it overrides the value requested
+    /// in the `ClientConfig` properties. Value 0 means the session ends with the connection.
+    pub fn session_expiry_interval(&self) -> u32 {
+        self.server_session_expiry_interval.unwrap_or_else(|| {
+            self.config
+                .properties
+                .iter()
+                .find_map(|prop| match prop {
+                    Property::SessionExpiryInterval(interval) => Some(*interval),
+                    _ => None,
+                })
+                .unwrap_or(0)
+        })
+    }
+
+    /// Returns the highest QoS which can be used for the connection. It is the lower of the
+    /// Maximum QoS sent by the broker in the CONNACK and QoS 1, because the client does not
+    /// support QoS 2 flows.
+    pub fn maximum_qos(&self) -> QualityOfService {
+        if self.server_maximum_qos == QualityOfService::QoS0 {
+            QualityOfService::QoS0
+        } else {
+            QualityOfService::QoS1
+        }
+    }
+
+    /// Fails with `QoSNotSupported` if the maximum QoS of the connection is lower than
+    /// the QoS required in the `ClientConfig`. Caller has to disconnect in that case.
+    fn check_required_qos(&self) -> Result<(), ReasonCode> {
+        if u8::from(self.maximum_qos()) < u8::from(self.config.required_qos) {
+            error!("Broker does not support the required QoS!");
+            return Err(ReasonCode::QoSNotSupported);
         }
+        Ok(())
+    }
+
+    /// Returns the packet and byte counters of the current network connection.
+    /// Counters start from zero with every new connection.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> ClientStats {
+        self.connection
+            .as_ref()
+            .map(|conn| conn.stats())
+            .unwrap_or_default()
+    }
+
+    /// Sets the hook which observes the bytes of all packets sent and received by the
+    /// client. The hook is kept by `set_network`, passing `None` removes it.
+    #[cfg(feature = "wire-hook")]
+    pub fn set_wire_hook(&mut self, hook: Option<WireHook<'a>>) {
+        self.wire_hook = hook;
+    }
+
+    /// Generates packet identifier from the rng. Value 0 is not a valid packet
+    /// identifier, so it is skipped.
+    fn packet_identifier(&mut self) -> u16 {
+        loop {
+            let identifier = self.config.rng.next_u32() as u16;
+            if identifier != 0 {
+                return identifier;
+            }
+        }
+    }
+
+    /// Returns the reason code of the DISCONNECT which closed the last connection, sent by
+    /// the client or received from the broker, or `NetworkError` if the network failed.
+    /// Returns `None` if the connection was not closed since the last connect.
+    pub fn last_close_reason(&self) -> Option<ReasonCode> {
+        self.close_reason
+    }
+
+    /// Returns the flags of the fixed header of the last received PUBLISH, the lower four
+    /// bits with DUP, QoS and RETAIN, exactly as the broker sent them, including the
+    /// reserved QoS value 3. Returns `None` if no PUBLISH was received yet.
+    pub fn last_publish_flags(&self) -> Option<u8> {
+        self.publish_flags
+    }
+
+    /// Replaces the network connection so the client can connect again. The previous
+    /// connection is dropped without sending DISCONNECT.
+    pub fn set_network(&mut self, network_driver: T) {
+        self.connection = Some(NetworkConnection::new(network_driver));
+        self.connected = false;
+    }
+
+    /// Returns true if the broker accepted the connection and it was not closed
+    /// by the client, by the broker or by a network error since then.
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some() && self.connected
     }
 
     async fn connect_to_broker_v5<'b>(&'b mut self) -> Result<(), ReasonCode> {
@@ -94,6 +275,49 @@ where
                     self.config.will_retain,
                 )
             }
+            if self.config.client_id.len == 0 && !self.assigned_client_id.is_empty() {
+                // Present the identifier assigned by the broker, so the session can be resumed
+                let mut client_id = EncodedString::new();
+                client_id.string = self.assigned_client_id.as_str();
+                client_id.len = self.assigned_client_id.len() as u16;
+                connect.add_client_id(&client_id);
+            } else {
+                connect.add_client_id(&self.config.client_id);
+            }
+            connect.encode(self.buffer, self.buffer_len)
+        };
+
+        if let Err(err) = len {
+            error!("[DECODE ERR]: {}", err);
+            return Err(ReasonCode::BuffError);
+        }
+        let conn = self.connection.as_mut().unwrap();
+        trace!("Sending connect");
+        send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await?;
+
+        Ok(())
+    }
+
+    async fn connect_to_broker_v3<'b>(&'b mut self) -> Result<(), ReasonCode> {
+        if self.connection.is_none() {
+            return Err(ReasonCode::NetworkError);
+        }
+        let len = {
+            let mut connect = v3::connect_packet::ConnectPacket::<'b>::new();
+            connect.keep_alive = self.config.keep_alive;
+            if self.config.username_flag {
+                connect.add_username(&self.config.username);
+            }
+            if self.config.password_flag {
+                connect.add_password(&self.config.password)
+            }
+            if self.config.will_flag {
+                connect.add_will(
+                    &self.config.will_topic,
+                    &self.config.will_payload,
+                    self.config.will_retain,
+                )
+            }
             connect.add_client_id(&self.config.client_id);
             connect.encode(self.buffer, self.buffer_len)
         };
@@ -104,7 +328,7 @@ where
         }
         let conn = self.connection.as_mut().unwrap();
         trace!("Sending connect");
-        conn.send(&self.buffer[0..len.unwrap()]).await?;
+        send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await?;
 
         Ok(())
     }
@@ -114,19 +338,22 @@ where
     /// If the connection to the broker fails, method returns Err variable that contains
     /// Reason codes returned from the broker.
     pub async fn connect_to_broker<'b>(&'b mut self) -> Result<(), ReasonCode> {
+        self.connected = false;
+        self.close_reason = None;
         match self.config.mqtt_version {
-            MqttVersion::MQTTv3 => Err(ReasonCode::UnsupportedProtocolVersion),
+            MqttVersion::MQTTv3 => self.connect_to_broker_v3().await,
             MqttVersion::MQTTv5 => self.connect_to_broker_v5().await,
         }
     }
 
-    async fn disconnect_v5<'b>(&'b mut self) -> Result<(), ReasonCode> {
+    async fn disconnect_v5<'b>(&'b mut self, reason: ReasonCode) -> Result<(), ReasonCode> {
         if self.connection.is_none() {
             return Err(ReasonCode::NetworkError);
         }
         let conn = self.connection.as_mut().unwrap();
         trace!("Creating disconnect packet!");
         let mut disconnect = DisconnectPacket::<'b, MAX_PROPERTIES>::new();
+        disconnect.add_reason(reason.into());
         let len = disconnect.encode(self.buffer, self.buffer_len);
         if let Err(err) = len {
             warn!("[DECODE ERR]: {}", err);
@@ -134,12 +361,41 @@ where
             return Err(ReasonCode::BuffError);
         }
 
-        if let Err(_e) = conn.send(&self.buffer[0..len.unwrap()]).await {
+        if let Err(_e) = send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await
+        {
             warn!("Could not send DISCONNECT packet");
         }
 
         // Drop connection
         let _ = self.connection.take();
+        self.connected = false;
+        self.close_reason = Some(reason);
+        Ok(())
+    }
+
+    async fn disconnect_v3<'b>(&'b mut self) -> Result<(), ReasonCode> {
+        if self.connection.is_none() {
+            return Err(ReasonCode::NetworkError);
+        }
+        let conn = self.connection.as_mut().unwrap();
+        trace!("Creating disconnect packet!");
+        let mut disconnect = v3::disconnect_packet::DisconnectPacket::new();
+        let len = disconnect.encode(self.buffer, self.buffer_len);
+        if let Err(err) = len {
+            warn!("[DECODE ERR]: {}", err);
+            let _ = self.connection.take();
+            return Err(ReasonCode::BuffError);
+        }
+
+        if let Err(_e) = send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await
+        {
+            warn!("Could not send DISCONNECT packet");
+        }
+
+        // Drop connection
+        let _ = self.connection.take();
+        self.connected = false;
+        self.close_reason = Some(ReasonCode::Success);
         Ok(())
     }
 
@@ -149,8 +405,31 @@ where
     /// Reason codes returned from the broker.
     pub async fn disconnect<'b>(&'b mut self) -> Result<(), ReasonCode> {
         match self.config.mqtt_version {
-            MqttVersion::MQTTv3 => Err(ReasonCode::UnsupportedProtocolVersion),
-            MqttVersion::MQTTv5 => self.disconnect_v5().await,
+            MqttVersion::MQTTv3 => self.disconnect_v3().await,
+            MqttVersion::MQTTv5 => self.disconnect_v5(ReasonCode::Success).await,
+        }
+    }
+
+    /// Method disconnects from the broker and tells it the `reason` in the DISCONNECT packet.
+    /// Reason has to be allowed for the client (see `ReasonCode::allowed_in_client_disconnect`),
+    /// otherwise `InvalidArgument` is returned and the connection is kept. MQTTv3 DISCONNECT
+    /// does not carry a reason, so only `Success` can be sent, other reasons fail with
+    /// `UnsupportedProtocolVersion` and the connection is kept.
+    pub async fn disconnect_with_reason<'b>(
+        &'b mut self,
+        reason: ReasonCode,
+    ) -> Result<(), ReasonCode> {
+        if !reason.allowed_in_client_disconnect() {
+            error!("Reason code cannot be sent by the client in DISCONNECT packet!");
+            return Err(ReasonCode::InvalidArgument);
+        }
+        match self.config.mqtt_version {
+            MqttVersion::MQTTv3 if reason != ReasonCode::Success => {
+                error!("MQTTv3 DISCONNECT cannot carry the reason code!");
+                Err(ReasonCode::UnsupportedProtocolVersion)
+            }
+            MqttVersion::MQTTv3 => self.disconnect_v3().await,
+            MqttVersion::MQTTv5 => self.disconnect_v5(reason).await,
         }
     }
 
@@ -160,13 +439,12 @@ where
         message: &'b [u8],
         qos: QualityOfService,
         retain: bool,
+        identifier: u16,
     ) -> Result<u16, ReasonCode> {
         if self.connection.is_none() {
             return Err(ReasonCode::NetworkError);
         }
         let conn = self.connection.as_mut().unwrap();
-        let identifier: u16 = self.config.rng.next_u32() as u16;
-        //self.rng.next_u32() as u16;
         let len = {
             let mut packet = PublishPacket::<'b, MAX_PROPERTIES>::new();
             packet.add_topic_name(topic_name);
@@ -182,10 +460,43 @@ where
             return Err(ReasonCode::BuffError);
         }
         trace!("Sending message");
-        conn.send(&self.buffer[0..len.unwrap()]).await?;
+        send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await?;
+
+        Ok(identifier)
+    }
+
+    async fn send_message_v3<'b>(
+        &'b mut self,
+        topic_name: &'b str,
+        message: &'b [u8],
+        qos: QualityOfService,
+        retain: bool,
+        identifier: u16,
+    ) -> Result<u16, ReasonCode> {
+        if self.connection.is_none() {
+            return Err(ReasonCode::NetworkError);
+        }
+        let conn = self.connection.as_mut().unwrap();
+        let len = {
+            let mut packet = v3::publish_packet::PublishPacket::<'b>::new();
+            packet.add_topic_name(topic_name);
+            packet.add_qos(qos);
+            packet.add_identifier(identifier);
+            packet.add_message(message);
+            packet.add_retain(retain);
+            packet.encode(self.buffer, self.buffer_len)
+        };
+
+        if let Err(err) = len {
+            error!("[DECODE ERR]: {}", err);
+            return Err(ReasonCode::BuffError);
+        }
+        trace!("Sending message");
+        send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await?;
 
         Ok(identifier)
     }
+
     /// Method allows sending message to broker specified from the ClientConfig. Client sends the
     /// message from the parameter `message` to the topic `topic_name` on the broker
     /// specified in the ClientConfig. If the send fails method returns Err with reason code
@@ -197,9 +508,63 @@ where
         qos: QualityOfService,
         retain: bool,
     ) -> Result<u16, ReasonCode> {
+        let identifier = self.packet_identifier();
+        self.send_message_with_identifier(topic_name, message, qos, retain, identifier)
+            .await
+    }
+
+    /// Returns the number of bytes of the PUBLISH packet which `send_message` sends for the
+    /// topic `topic_name` and the message with `message_len` bytes using the `qos`.
+    pub fn publish_size(
+        &self,
+        topic_name: &str,
+        message_len: usize,
+        qos: QualityOfService,
+    ) -> Result<usize, ReasonCode> {
+        let len = match self.config.mqtt_version {
+            MqttVersion::MQTTv3 => {
+                let mut packet = v3::publish_packet::PublishPacket::new();
+                packet.add_topic_name(topic_name);
+                packet.add_qos(qos);
+                packet.encoded_len_with_message_len(message_len)
+            }
+            MqttVersion::MQTTv5 => {
+                let mut packet = PublishPacket::<'_, MAX_PROPERTIES>::new();
+                packet.add_topic_name(topic_name);
+                packet.add_qos(qos);
+                packet.encoded_len_with_message_len(message_len)
+            }
+        };
+        len.map_err(|_| {
+            error!("Message is too large to be published!");
+            ReasonCode::PacketTooLarge
+        })
+    }
+
+    /// Method sends the message like `send_message`, but uses the packet `identifier` provided
+    /// by the caller instead of generating one, e.g. to keep the identifier of a bridged message.
+    /// Identifier 0 is not valid and is rejected with `ProtocolError`.
+    pub async fn send_message_with_identifier<'b>(
+        &'b mut self,
+        topic_name: &'b str,
+        message: &'b [u8],
+        qos: QualityOfService,
+        retain: bool,
+        identifier: u16,
+    ) -> Result<u16, ReasonCode> {
+        if identifier == 0 {
+            error!("Packet identifier 0 is not valid!");
+            return Err(ReasonCode::ProtocolError);
+        }
         match self.config.mqtt_version {
-            MqttVersion::MQTTv3 => Err(ReasonCode::UnsupportedProtocolVersion),
-            MqttVersion::MQTTv5 => self.send_message_v5(topic_name, message, qos, retain).await,
+            MqttVersion::MQTTv3 => {
+                self.send_message_v3(topic_name, message, qos, retain, identifier)
+                    .await
+            }
+            MqttVersion::MQTTv5 => {
+                self.send_message_v5(topic_name, message, qos, retain, identifier)
+                    .await
+            }
         }
     }
 
@@ -210,11 +575,48 @@ where
         if self.connection.is_none() {
             return Err(ReasonCode::NetworkError);
         }
+        let identifier: u16 = self.packet_identifier();
         let conn = self.connection.as_mut().unwrap();
-        let identifier: u16 = self.config.rng.next_u32() as u16;
         let len = {
             let mut subs = SubscriptionPacket::<'b, TOPICS, MAX_PROPERTIES>::new();
             subs.packet_identifier = identifier;
+            if let Some(subscription_identifier) = self.config.subscription_identifier {
+                let mut props = Vec::<Property<'b>, 1>::new();
+                let _ = props.push(Property::SubscriptionIdentifier(subscription_identifier));
+                subs.property_len = subs.add_properties(&props);
+            }
+            for topic_name in topic_names.iter() {
+                subs.add_new_filter_with_retain_handling(
+                    topic_name,
+                    self.config.max_subscribe_qos,
+                    self.config.retain_handling,
+                );
+            }
+            subs.encode(self.buffer, self.buffer_len)
+        };
+
+        if let Err(err) = len {
+            error!("[DECODE ERR]: {}", err);
+            return Err(ReasonCode::BuffError);
+        }
+
+        send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await?;
+
+        Ok(identifier)
+    }
+
+    async fn subscribe_to_topics_v3<'b, const TOPICS: usize>(
+        &'b mut self,
+        topic_names: &'b Vec<&'b str, TOPICS>,
+    ) -> Result<u16, ReasonCode> {
+        if self.connection.is_none() {
+            return Err(ReasonCode::NetworkError);
+        }
+        let identifier: u16 = self.packet_identifier();
+        let conn = self.connection.as_mut().unwrap();
+        let len = {
+            let mut subs = v3::subscription_packet::SubscriptionPacket::<'b, TOPICS>::new();
+            subs.packet_identifier = identifier;
             for topic_name in topic_names.iter() {
                 subs.add_new_filter(topic_name, self.config.max_subscribe_qos);
             }
@@ -226,7 +628,7 @@ where
             return Err(ReasonCode::BuffError);
         }
 
-        conn.send(&self.buffer[0..len.unwrap()]).await?;
+        send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await?;
 
         Ok(identifier)
     }
@@ -239,8 +641,31 @@ where
         &'b mut self,
         topic_names: &'b Vec<&'b str, TOPICS>,
     ) -> Result<u16, ReasonCode> {
+        for topic_name in topic_names.iter() {
+            if !topic_filter::is_valid_shared(topic_name) {
+                return Err(ReasonCode::TopicFilterInvalid);
+            }
+            if topic_filter::is_shared(topic_name) && !self.shared_subscription_available {
+                return Err(ReasonCode::SharedSubscriptionNotSupported);
+            }
+            if topic_filter::has_wildcards(topic_name) && !self.wildcard_subscription_available {
+                return Err(ReasonCode::WildcardSubscriptionNotSupported);
+            }
+        }
+        if let (MqttVersion::MQTTv5, Some(subscription_identifier)) = (
+            &self.config.mqtt_version,
+            self.config.subscription_identifier,
+        ) {
+            if !is_valid_subscription_identifier(subscription_identifier) {
+                error!("Subscription identifier is out of range!");
+                return Err(ReasonCode::InvalidArgument);
+            }
+            if !self.subscription_identifiers_available {
+                return Err(ReasonCode::SubscriptionIdentifiersNotSupported);
+            }
+        }
         match self.config.mqtt_version {
-            MqttVersion::MQTTv3 => Err(ReasonCode::UnsupportedProtocolVersion),
+            MqttVersion::MQTTv3 => self.subscribe_to_topics_v3(topic_names).await,
             MqttVersion::MQTTv5 => self.subscribe_to_topics_v5(topic_names).await,
         }
     }
@@ -253,7 +678,7 @@ where
         topic_name: &'b str,
     ) -> Result<u16, ReasonCode> {
         match self.config.mqtt_version {
-            MqttVersion::MQTTv3 => Err(ReasonCode::UnsupportedProtocolVersion),
+            MqttVersion::MQTTv3 => self.unsubscribe_from_topic_v3(topic_name).await,
             MqttVersion::MQTTv5 => self.unsubscribe_from_topic_v5(topic_name).await,
         }
     }
@@ -265,8 +690,8 @@ where
         if self.connection.is_none() {
             return Err(ReasonCode::NetworkError);
         }
+        let identifier = self.packet_identifier();
         let conn = self.connection.as_mut().unwrap();
-        let identifier = self.config.rng.next_u32() as u16;
 
         let len = {
             let mut unsub = UnsubscriptionPacket::<'b, 1, MAX_PROPERTIES>::new();
@@ -279,7 +704,33 @@ where
             error!("[DECODE ERR]: {}", err);
             return Err(ReasonCode::BuffError);
         }
-        conn.send(&self.buffer[0..len.unwrap()]).await?;
+        send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await?;
+
+        Ok(identifier)
+    }
+
+    async fn unsubscribe_from_topic_v3<'b>(
+        &'b mut self,
+        topic_name: &'b str,
+    ) -> Result<u16, ReasonCode> {
+        if self.connection.is_none() {
+            return Err(ReasonCode::NetworkError);
+        }
+        let identifier = self.packet_identifier();
+        let conn = self.connection.as_mut().unwrap();
+
+        let len = {
+            let mut unsub = v3::unsubscription_packet::UnsubscriptionPacket::<'b, 1>::new();
+            unsub.packet_identifier = identifier;
+            unsub.add_new_filter(topic_name);
+            unsub.encode(self.buffer, self.buffer_len)
+        };
+
+        if let Err(err) = len {
+            error!("[DECODE ERR]: {}", err);
+            return Err(ReasonCode::BuffError);
+        }
+        send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await?;
 
         Ok(identifier)
     }
@@ -299,7 +750,7 @@ where
             return Err(ReasonCode::BuffError);
         }
 
-        conn.send(&self.buffer[0..len.unwrap()]).await?;
+        send_packet(conn, &mut self.wire_hook, &self.buffer[0..len.unwrap()]).await?;
 
         Ok(())
     }
@@ -309,12 +760,164 @@ where
     /// regularly by the timer that counts down the session expiry interval.
     pub async fn send_ping<'b>(&'b mut self) -> Result<(), ReasonCode> {
         match self.config.mqtt_version {
-            MqttVersion::MQTTv3 => Err(ReasonCode::UnsupportedProtocolVersion),
-            MqttVersion::MQTTv5 => self.send_ping_v5().await,
+            // PINGREQ has the same format in both versions of the protocol
+            MqttVersion::MQTTv3 | MqttVersion::MQTTv5 => self.send_ping_v5().await,
         }
     }
 
+    /// Method waits for the next packet from the broker and maps it to the `Event`. Packets
+    /// are decoded based on the MQTT version set in the `ClientConfig`.
     pub async fn poll<'b, const MAX_TOPICS: usize>(&'b mut self) -> Result<Event<'b>, ReasonCode> {
+        match self.config.mqtt_version {
+            MqttVersion::MQTTv3 => self.poll_v3::<MAX_TOPICS>().await,
+            MqttVersion::MQTTv5 => self.poll_v5::<MAX_TOPICS>().await,
+        }
+    }
+
+    async fn poll_v3<'b, const MAX_TOPICS: usize>(&'b mut self) -> Result<Event<'b>, ReasonCode> {
+        if self.connection.is_none() {
+            return Err(ReasonCode::NetworkError);
+        }
+
+        let conn = self.connection.as_mut().unwrap();
+
+        trace!("Waiting for a packet");
+
+        // MQTTv3 has no Maximum Packet Size, the packet is limited by the buffer only
+        let read = match receive_packet(
+            self.buffer,
+            self.buffer_len,
+            self.recv_buffer,
+            conn,
+            usize::MAX,
+        )
+        .await
+        {
+            Ok(read) => read,
+            Err(ReasonCode::NetworkError) => {
+                self.connected = false;
+                self.close_reason = Some(ReasonCode::NetworkError);
+                return Err(ReasonCode::NetworkError);
+            }
+            Err(err) => return Err(err),
+        };
+
+        #[cfg(feature = "metrics")]
+        conn.packet_received(self.buffer[0]);
+        #[cfg(feature = "wire-hook")]
+        if let Some(hook) = self.wire_hook.as_mut() {
+            hook(Direction::Received, &self.buffer[0..read]);
+        }
+
+        let buf_reader = BuffReader::new(self.buffer, read);
+
+        match PacketType::from(buf_reader.peek_u8().map_err(|_| ReasonCode::BuffError)?) {
+            PacketType::Reserved
+            | PacketType::Connect
+            | PacketType::Subscribe
+            | PacketType::Unsubscribe
+            | PacketType::Pingreq
+            | PacketType::Disconnect
+            | PacketType::Auth => Err(ReasonCode::ProtocolError),
+            PacketType::Pubrec | PacketType::Pubrel | PacketType::Pubcomp => {
+                Err(ReasonCode::ImplementationSpecificError)
+            }
+            PacketType::Connack => {
+                let mut packet = v3::connack_packet::ConnackPacket::new();
+                if let Err(err) = packet.decode(&mut BuffReader::new(self.buffer, read)) {
+                    error!("[DECODE ERR]: {}", err);
+                    Err(ReasonCode::BuffError)
+                } else if packet.return_code != 0x00 {
+                    Err(packet.reason_code())
+                } else {
+                    if let Err(err) = self.check_required_qos() {
+                        self.disconnect_v3().await?;
+                        return Err(err);
+                    }
+                    self.connected = true;
+                    Ok(Event::Connack)
+                }
+            }
+            PacketType::Puback => {
+                let mut packet = v3::puback_packet::PubackPacket::new();
+                if let Err(err) = packet.decode(&mut BuffReader::new(self.buffer, read)) {
+                    error!("[DECODE ERR]: {}", err);
+                    Err(ReasonCode::BuffError)
+                } else {
+                    Ok(Event::Puback(packet.packet_identifier, ReasonCode::Success))
+                }
+            }
+            PacketType::Suback => {
+                let mut packet = v3::suback_packet::SubackPacket::<MAX_TOPICS>::new();
+                if let Err(err) = packet.decode(&mut BuffReader::new(self.buffer, read)) {
+                    error!("[DECODE ERR]: {}", err);
+                    return Err(ReasonCode::BuffError);
+                }
+                for return_code in &packet.return_codes {
+                    if *return_code
+                        != (<QualityOfService as Into<u8>>::into(self.config.max_subscribe_qos)
+                            >> 1)
+                    {
+                        return Err(ReasonCode::from(*return_code));
+                    }
+                }
+                Ok(Event::Suback(packet.packet_identifier))
+            }
+            PacketType::Unsuback => {
+                let mut packet = v3::unsuback_packet::UnsubackPacket::new();
+                if let Err(err) = packet.decode(&mut BuffReader::new(self.buffer, read)) {
+                    error!("[DECODE ERR]: {}", err);
+                    Err(ReasonCode::BuffError)
+                } else {
+                    Ok(Event::Unsuback(packet.packet_identifier))
+                }
+            }
+            PacketType::Pingresp => {
+                let mut packet = PingrespPacket::new();
+                if let Err(err) = packet.decode(&mut BuffReader::new(self.buffer, read)) {
+                    error!("[DECODE ERR]: {}", err);
+                    Err(ReasonCode::BuffError)
+                } else {
+                    Ok(Event::Pingresp)
+                }
+            }
+            PacketType::Publish => {
+                let mut packet = v3::publish_packet::PublishPacket::<'b>::new();
+                if let Err(err) = packet.decode(&mut BuffReader::new(self.buffer, read)) {
+                    error!("[DECODE ERR]: {}", err);
+                    return Err(ReasonCode::BuffError);
+                }
+                self.publish_flags = Some(packet.fixed_header & 0x0F);
+
+                if (packet.fixed_header & 0x06)
+                    == <QualityOfService as Into<u8>>::into(QualityOfService::QoS1)
+                {
+                    let mut puback = v3::puback_packet::PubackPacket::new();
+                    puback.packet_identifier = packet.packet_identifier;
+                    {
+                        let len = { puback.encode(self.recv_buffer, self.recv_buffer_len) };
+                        if let Err(err) = len {
+                            error!("[DECODE ERR]: {}", err);
+                            return Err(ReasonCode::BuffError);
+                        }
+                        send_packet(
+                            conn,
+                            &mut self.wire_hook,
+                            &self.recv_buffer[0..len.unwrap()],
+                        )
+                        .await?;
+                    }
+                }
+
+                Ok(Event::Message(
+                    packet.topic_name.string,
+                    packet.message.unwrap(),
+                ))
+            }
+        }
+    }
+
+    async fn poll_v5<'b, const MAX_TOPICS: usize>(&'b mut self) -> Result<Event<'b>, ReasonCode> {
         if self.connection.is_none() {
             return Err(ReasonCode::NetworkError);
         }
@@ -323,7 +926,35 @@ where
 
         trace!("Waiting for a packet");
 
-        let read = { receive_packet(self.buffer, self.buffer_len, self.recv_buffer, conn).await? };
+        let read = match receive_packet(
+            self.buffer,
+            self.buffer_len,
+            self.recv_buffer,
+            conn,
+            self.config.max_packet_size as usize,
+        )
+        .await
+        {
+            Ok(read) => read,
+            Err(ReasonCode::NetworkError) => {
+                self.connected = false;
+                self.close_reason = Some(ReasonCode::NetworkError);
+                return Err(ReasonCode::NetworkError);
+            }
+            Err(ReasonCode::PacketTooLarge) => {
+                // Rest of the packet was not read, the connection cannot be used anymore
+                self.disconnect_v5(ReasonCode::PacketTooLarge).await?;
+                return Err(ReasonCode::PacketTooLarge);
+            }
+            Err(err) => return Err(err),
+        };
+
+        #[cfg(feature = "metrics")]
+        conn.packet_received(self.buffer[0]);
+        #[cfg(feature = "wire-hook")]
+        if let Some(hook) = self.wire_hook.as_mut() {
+            hook(Direction::Received, &self.buffer[0..read]);
+        }
 
         let buf_reader = BuffReader::new(self.buffer, read);
 
@@ -351,6 +982,59 @@ where
                 } else if packet.connect_reason_code != 0x00 {
                     Err(ReasonCode::from(packet.connect_reason_code))
                 } else {
+                    // Features are available unless the broker says otherwise
+                    self.shared_subscription_available = true;
+                    self.wildcard_subscription_available = true;
+                    self.subscription_identifiers_available = true;
+                    self.server_keep_alive = None;
+                    self.server_session_expiry_interval = None;
+                    self.response_information.clear();
+                    self.server_maximum_qos = QualityOfService::QoS2;
+                    for prop in packet.properties.iter() {
+                        match prop {
+                            Property::SharedSubscriptionAvailable(available) => {
+                                self.shared_subscription_available = *available == 1;
+                            }
+                            Property::WildcardSubscriptionAvailable(available) => {
+                                self.wildcard_subscription_available = *available == 1;
+                            }
+                            Property::SubscriptionIdentifierAvailable(available) => {
+                                self.subscription_identifiers_available = *available == 1;
+                            }
+                            Property::ServerKeepAlive(keep_alive) => {
+                                self.server_keep_alive = Some(*keep_alive);
+                            }
+                            Property::SessionExpiryInterval(interval) => {
+                                self.server_session_expiry_interval = Some(*interval);
+                            }
+                            Property::MaximumQoS(qos) => {
+                                self.server_maximum_qos = if *qos == 0 {
+                                    QualityOfService::QoS0
+                                } else {
+                                    QualityOfService::QoS1
+                                };
+                            }
+                            _ => {}
+                        }
+                    }
+                    if let Some(id) = packet.assigned_client_identifier() {
+                        self.assigned_client_id.clear();
+                        if self.assigned_client_id.push_str(id).is_err() {
+                            warn!("Assigned client identifier is too long to be stored!");
+                        }
+                    }
+                    if let Some(info) = packet.response_information() {
+                        if self.response_information.push_str(info).is_err() {
+                            warn!("Response information is too long to be stored!");
+                        }
+                    }
+                    drop(packet);
+                    if let Err(err) = self.check_required_qos() {
+                        // Broker considers the session established, so it is closed properly
+                        self.disconnect_v5(ReasonCode::QoSNotSupported).await?;
+                        return Err(err);
+                    }
+                    self.connected = true;
                     Ok(Event::Connack)
                 }
             }
@@ -368,12 +1052,18 @@ where
                 }
 
                 let res = reason.unwrap();
+                let reason_code = ReasonCode::from(res[1] as u8);
 
-                if res[1] != 0 {
-                    return Err(ReasonCode::from(res[1] as u8));
+                // Message was accepted by the broker even if nobody is subscribed,
+                // so it must not be treated as a failure.
+                if reason_code.is_error() {
+                    return Err(reason_code);
+                }
+                if !reason_code.is_success() {
+                    warn!("Publish {} acknowledged with: {}", res[0], reason_code);
                 }
 
-                Ok(Event::Puback(res[0]))
+                Ok(Event::Puback(res[0], reason_code))
             }
             PacketType::Suback => {
                 let reason: Result<(u16, Vec<u8, MAX_TOPICS>), BufferError> = {
@@ -433,8 +1123,12 @@ where
                     //     }
                     // }
                     error!("[DECODE ERR]: {}", err);
+                    if err == BufferError::ProtocolError {
+                        return Err(ReasonCode::ProtocolError);
+                    }
                     return Err(ReasonCode::BuffError);
                 }
+                self.publish_flags = Some(packet.fixed_header & 0x0F);
 
                 if (packet.fixed_header & 0x06)
                     == <QualityOfService as Into<u8>>::into(QualityOfService::QoS1)
@@ -448,7 +1142,12 @@ where
                             error!("[DECODE ERR]: {}", err);
                             return Err(ReasonCode::BuffError);
                         }
-                        conn.send(&self.recv_buffer[0..len.unwrap()]).await?;
+                        send_packet(
+                            conn,
+                            &mut self.wire_hook,
+                            &self.recv_buffer[0..len.unwrap()],
+                        )
+                        .await?;
                     }
                 }
 
@@ -462,7 +1161,17 @@ where
                 let res = disc.decode(&mut BuffReader::new(self.buffer, read));
 
                 match res {
-                    Ok(_) => Ok(Event::Disconnect(ReasonCode::from(disc.disconnect_reason))),
+                    Ok(_) => {
+                        self.connected = false;
+                        let reason = ReasonCode::from(disc.disconnect_reason);
+                        self.close_reason = Some(reason);
+                        if reason.is_redirect() {
+                            if let Some(reference) = disc.server_reference() {
+                                warn!("Broker redirects client to: {}", reference);
+                            }
+                        }
+                        Ok(Event::Disconnect(reason))
+                    }
                     Err(err) => {
                         error!("[DECODE ERR]: {}", err);
                         Err(ReasonCode::BuffError)
@@ -496,12 +1205,33 @@ where
     }
 }
 
+/// Slot of the wire hook, empty when the `wire-hook` feature is disabled.
+#[cfg(feature = "wire-hook")]
+type WireHookSlot<'a> = Option<WireHook<'a>>;
+#[cfg(not(feature = "wire-hook"))]
+type WireHookSlot<'a> = ();
+
+/// Sends the complete `packet` and passes it to the wire hook.
+async fn send_packet<T: Read + Write>(
+    conn: &mut NetworkConnection<T>,
+    _wire_hook: &mut WireHookSlot<'_>,
+    packet: &[u8],
+) -> Result<(), ReasonCode> {
+    conn.send(packet).await?;
+    #[cfg(feature = "wire-hook")]
+    if let Some(hook) = _wire_hook.as_mut() {
+        hook(Direction::Sent, packet);
+    }
+    Ok(())
+}
+
 #[cfg(not(feature = "tls"))]
 async fn receive_packet<'c, T: Read + Write>(
     buffer: &mut [u8],
     buffer_len: usize,
     recv_buffer: &mut [u8],
     conn: &'c mut NetworkConnection<T>,
+    max_packet_size: usize,
 ) -> Result<usize, ReasonCode> {
     use crate::utils::buffer_writer::RemLenError;
 
@@ -548,6 +1278,14 @@ async fn receive_packet<'c, T: Read + Write>(
         error!("Could not decode len of packet!");
         return Err(ReasonCode::BuffError);
     }
+    if target_len + rem_len_len > max_packet_size {
+        error!("Received packet exceeds the Maximum Packet Size!");
+        return Err(ReasonCode::PacketTooLarge);
+    }
+    if target_len + rem_len_len > buffer_len || target_len + rem_len_len > recv_buffer.len() {
+        error!("Received packet does not fit into the buffer!");
+        return Err(ReasonCode::BuffError);
+    }
 
     loop {
         if writer.position == target_len + rem_len_len {
@@ -573,10 +1311,19 @@ async fn receive_packet<'c, T: Read + Write>(
     buffer_len: usize,
     recv_buffer: &mut [u8],
     conn: &'c mut NetworkConnection<T>,
+    max_packet_size: usize,
 ) -> Result<usize, ReasonCode> {
     trace!("Reading packet");
     let mut writer = BuffWriter::new(buffer, buffer_len);
     let len = conn.receive(recv_buffer).await?;
+    if len == 0 {
+        trace!("Zero byte len packet received, dropping connection.");
+        return Err(ReasonCode::NetworkError);
+    }
+    if len > max_packet_size {
+        error!("Received packet exceeds the Maximum Packet Size!");
+        return Err(ReasonCode::PacketTooLarge);
+    }
     if let Err(_e) = writer.insert_ref(len, &recv_buffer[writer.position..(writer.position + len)])
     {
         error!("Error occurred during write to buffer!");
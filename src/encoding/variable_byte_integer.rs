@@ -33,6 +33,11 @@ use crate::utils::types::BufferError;
 /// lenghts stored in a packet.
 pub struct VariableByteIntegerEncoder;
 
+impl VariableByteIntegerEncoder {
+    /// Maximal value which can be encoded into 4 Bytes.
+    pub const MAX_ENCODABLE: u32 = 268_435_455;
+}
+
 /// Variable byte integers error enumeration is used by both encoder and decoder for
 /// error notification.
 
@@ -45,9 +50,8 @@ impl VariableByteIntegerEncoder {
     /// we can effectively use 1 to 4 Bytes based in integer len.
     pub fn encode(mut target: u32) -> Result<VariableByteInteger, BufferError> {
         // General known informations from OASIS
-        const MAX_ENCODABLE: u32 = 268435455;
         const MOD: u32 = 128;
-        if target > MAX_ENCODABLE {
+        if target > Self::MAX_ENCODABLE {
             error!("Maximal value of integer for encoding was exceeded");
             return Err(BufferError::EncodingError);
         }
@@ -76,7 +80,7 @@ impl VariableByteIntegerEncoder {
         loop {
             let encoded_byte = var_int[i];
             i += 1;
-            if (encoded_byte & 128) == 0 {
+            if (encoded_byte & 128) == 0 || i == var_int.len() {
                 break;
             }
         }
@@ -100,16 +104,17 @@ impl VariableByteIntegerDecoder {
         let mut i: usize = 0;
 
         loop {
+            // Fourth Byte can not have the continuation bit set
+            if i == encoded.len() {
+                return Err(BufferError::DecodingError);
+            }
             encoded_byte = encoded[i];
             i += 1;
             ret += (encoded_byte & 127) as u32 * multiplier;
-            if multiplier > 128 * 128 * 128 {
-                return Err(BufferError::DecodingError);
-            }
-            multiplier *= 128;
             if (encoded_byte & 128) == 0 {
                 break;
             }
+            multiplier *= 128;
         }
 
         Ok(ret)
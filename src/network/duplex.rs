@@ -0,0 +1,190 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use core::cell::RefCell;
+use core::future::poll_fn;
+use core::task::{Poll, Waker};
+
+use embedded_io::{ErrorKind, ErrorType, ReadReady};
+use embedded_io_async::{Read, Write};
+use heapless::Deque;
+
+/// Bytes travelling in one direction of the `Duplex`.
+struct Pipe<const N: usize> {
+    data: Deque<u8, N>,
+    closed: bool,
+    reader: Option<Waker>,
+    writer: Option<Waker>,
+}
+
+impl<const N: usize> Pipe<N> {
+    const fn new() -> Self {
+        Self {
+            data: Deque::new(),
+            closed: false,
+            reader: None,
+            writer: None,
+        }
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        if let Some(waker) = self.reader.take() {
+            waker.wake();
+        }
+        if let Some(waker) = self.writer.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// In-memory connection with two connected ends. Bytes written to one end are read from
+/// the other one, so the `MqttClient` can use one end while a test plays the broker on the
+/// other end, without any network. Each direction buffers up to `N` bytes. The ends are
+/// not `Send`, both have to be polled from the same task, e.g. joined futures.
+pub struct Duplex<const N: usize> {
+    pipes: [RefCell<Pipe<N>>; 2],
+}
+
+impl<const N: usize> Duplex<N> {
+    pub const fn new() -> Self {
+        Self {
+            pipes: [RefCell::new(Pipe::new()), RefCell::new(Pipe::new())],
+        }
+    }
+
+    /// Returns the two connected ends. Data left from the previous split are discarded.
+    pub fn split(&mut self) -> (DuplexEnd<'_, N>, DuplexEnd<'_, N>) {
+        for pipe in self.pipes.iter() {
+            *pipe.borrow_mut() = Pipe::new();
+        }
+        let [first, second] = &self.pipes;
+        (
+            DuplexEnd {
+                rx: first,
+                tx: second,
+            },
+            DuplexEnd {
+                rx: second,
+                tx: first,
+            },
+        )
+    }
+}
+
+impl<const N: usize> Default for Duplex<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One end of the `Duplex`. When the other end is dropped, read returns the bytes which
+/// were already sent and then 0, write fails with `ErrorKind::BrokenPipe`.
+pub struct DuplexEnd<'a, const N: usize> {
+    rx: &'a RefCell<Pipe<N>>,
+    tx: &'a RefCell<Pipe<N>>,
+}
+
+impl<const N: usize> Drop for DuplexEnd<'_, N> {
+    fn drop(&mut self) {
+        self.rx.borrow_mut().close();
+        self.tx.borrow_mut().close();
+    }
+}
+
+impl<const N: usize> ErrorType for DuplexEnd<'_, N> {
+    type Error = ErrorKind;
+}
+
+impl<const N: usize> Read for DuplexEnd<'_, N> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        poll_fn(|cx| {
+            let mut pipe = self.rx.borrow_mut();
+            if pipe.data.is_empty() {
+                if pipe.closed {
+                    return Poll::Ready(Ok(0));
+                }
+                pipe.reader = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            let mut len = 0;
+            while len < buf.len() {
+                match pipe.data.pop_front() {
+                    Some(byte) => buf[len] = byte,
+                    None => break,
+                }
+                len += 1;
+            }
+            if let Some(waker) = pipe.writer.take() {
+                waker.wake();
+            }
+            Poll::Ready(Ok(len))
+        })
+        .await
+    }
+}
+
+impl<const N: usize> ReadReady for DuplexEnd<'_, N> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        let pipe = self.rx.borrow();
+        Ok(!pipe.data.is_empty() || pipe.closed)
+    }
+}
+
+impl<const N: usize> Write for DuplexEnd<'_, N> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        poll_fn(|cx| {
+            let mut pipe = self.tx.borrow_mut();
+            if pipe.closed {
+                return Poll::Ready(Err(ErrorKind::BrokenPipe));
+            }
+            if pipe.data.is_full() {
+                pipe.writer = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            let mut len = 0;
+            for byte in buf.iter() {
+                if pipe.data.push_back(*byte).is_err() {
+                    break;
+                }
+                len += 1;
+            }
+            if let Some(waker) = pipe.reader.take() {
+                waker.wake();
+            }
+            Poll::Ready(Ok(len))
+        })
+        .await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
@@ -22,15 +22,41 @@
  * SOFTWARE.
  */
 
+#[cfg(feature = "duplex")]
+pub mod duplex;
+#[cfg(feature = "metrics")]
+pub mod stats;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
 use crate::packet::v5::reason_codes::ReasonCode;
 use embedded_io::ReadReady;
 use embedded_io_async::{Read, Write};
 
+#[cfg(feature = "metrics")]
+use self::stats::ClientStats;
+
+/// Direction of the packet passed to the wire hook.
+#[cfg(feature = "wire-hook")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// Hook called with the bytes of every complete packet sent or received by the client.
+/// The closure can capture state of the caller, e.g. a log of the packets.
+#[cfg(feature = "wire-hook")]
+pub type WireHook<'a> = &'a mut dyn FnMut(Direction, &[u8]);
+
 pub struct NetworkConnection<T>
 where
     T: Read + Write,
 {
     io: T,
+    #[cfg(feature = "metrics")]
+    stats: ClientStats,
 }
 
 /// Network connection represents an established TCP connection.
@@ -40,7 +66,23 @@ where
 {
     /// Create a new network handle using the provided IO implementation.
     pub fn new(io: T) -> Self {
-        Self { io }
+        Self {
+            io,
+            #[cfg(feature = "metrics")]
+            stats: ClientStats::default(),
+        }
+    }
+
+    /// Returns the packet and byte counters of the connection.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> ClientStats {
+        self.stats
+    }
+
+    /// Counts the packet with the fixed header `header` as received.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn packet_received(&mut self, header: u8) {
+        self.stats.packet_received(header);
     }
 
     /// Send the data from `buffer` via TCP connection.
@@ -55,15 +97,22 @@ where
             .await
             .map_err(|_| ReasonCode::NetworkError)?;
 
+        #[cfg(feature = "metrics")]
+        self.stats.packet_sent(buffer);
         Ok(())
     }
 
     /// Receive data to the `buffer` from TCP connection.
     pub async fn receive(&mut self, buffer: &mut [u8]) -> Result<usize, ReasonCode> {
-        self.io
+        let len = self
+            .io
             .read(buffer)
             .await
-            .map_err(|_| ReasonCode::NetworkError)
+            .map_err(|_| ReasonCode::NetworkError)?;
+
+        #[cfg(feature = "metrics")]
+        self.stats.bytes_received(len);
+        Ok(len)
     }
 }
 
@@ -0,0 +1,79 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::packet::v5::packet_type::PacketType;
+
+/// Counters of the packets and bytes transferred over the network connection.
+/// Packets are counted per packet type, the index of the counter is the
+/// MQTT control packet type number (e.g. 3 for PUBLISH). `publish_in_flight`
+/// is the number of sent QoS 1 PUBLISH packets still waiting for PUBACK.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClientStats {
+    pub packets_sent: [u32; 16],
+    pub packets_received: [u32; 16],
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub publish_in_flight: u32,
+}
+
+impl ClientStats {
+    /// Returns number of sent packets of the `packet_type`.
+    pub fn sent(&self, packet_type: PacketType) -> u32 {
+        self.packets_sent[Self::index(packet_type)]
+    }
+
+    /// Returns number of received packets of the `packet_type`.
+    pub fn received(&self, packet_type: PacketType) -> u32 {
+        self.packets_received[Self::index(packet_type)]
+    }
+
+    pub(crate) fn packet_sent(&mut self, packet: &[u8]) {
+        if let Some(header) = packet.first() {
+            let index = (*header >> 4) as usize;
+            self.packets_sent[index] = self.packets_sent[index].wrapping_add(1);
+            // QoS bits of the PUBLISH fixed header
+            if index == Self::index(PacketType::Publish) && *header & 0x06 == 0x02 {
+                self.publish_in_flight = self.publish_in_flight.saturating_add(1);
+            }
+        }
+        self.bytes_sent = self.bytes_sent.wrapping_add(packet.len() as u64);
+    }
+
+    pub(crate) fn packet_received(&mut self, header: u8) {
+        let index = (header >> 4) as usize;
+        self.packets_received[index] = self.packets_received[index].wrapping_add(1);
+        if index == Self::index(PacketType::Puback) {
+            self.publish_in_flight = self.publish_in_flight.saturating_sub(1);
+        }
+    }
+
+    pub(crate) fn bytes_received(&mut self, len: usize) {
+        self.bytes_received = self.bytes_received.wrapping_add(len as u64);
+    }
+
+    fn index(packet_type: PacketType) -> usize {
+        (<PacketType as Into<u8>>::into(packet_type) >> 4) as usize
+    }
+}
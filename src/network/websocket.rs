@@ -0,0 +1,473 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use embedded_io::{ErrorKind, ErrorType, ReadExactError};
+use embedded_io_async::{Read, Write};
+use rand_core::RngCore;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+/// Close status code sent when the server violates the protocol.
+const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+
+/// Maximal length of the HTTP response accepted during the opening handshake.
+const MAX_HANDSHAKE_RESPONSE: usize = 1024;
+/// Control frames can carry at most 125 Bytes of payload.
+const MAX_CONTROL_PAYLOAD: usize = 125;
+/// GUID appended to the handshake key before computing the `Sec-WebSocket-Accept` value.
+const ACCEPT_GUID: &[u8] = b"258EAFA5-E914-47DA-95CA-C5AB0DC11B65";
+
+/// Errors returned by the `WebSocketConnection`.
+#[derive(Debug)]
+pub enum WebSocketError<E> {
+    /// Error returned by the underlying network implementation.
+    Io(E),
+    /// Server did not accept the upgrade to the WebSocket protocol.
+    Handshake,
+    /// Server sent a frame which is not valid according to RFC 6455.
+    Protocol,
+    /// Connection was closed before the whole frame was received.
+    ConnectionClosed,
+}
+
+impl<E: embedded_io::Error> embedded_io::Error for WebSocketError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            WebSocketError::Io(e) => e.kind(),
+            WebSocketError::Handshake => ErrorKind::ConnectionRefused,
+            WebSocketError::Protocol => ErrorKind::InvalidData,
+            WebSocketError::ConnectionClosed => ErrorKind::ConnectionAborted,
+        }
+    }
+}
+
+impl<E> From<ReadExactError<E>> for WebSocketError<E> {
+    fn from(value: ReadExactError<E>) -> Self {
+        match value {
+            ReadExactError::UnexpectedEof => WebSocketError::ConnectionClosed,
+            ReadExactError::Other(e) => WebSocketError::Io(e),
+        }
+    }
+}
+
+/// WebSocket connection wraps the byte stream `T` (usually TCP or TLS connection) and transfers
+/// the MQTT packets inside of the binary WebSocket frames with `mqtt` subprotocol. Every write
+/// is sent as a single masked frame. Received frames are exposed as a continuous byte stream,
+/// so MQTT packet fragmented into more frames is reassembled before it is decoded by the client.
+/// Connection has to be upgraded by calling `connect` before it is passed to the `MqttClient`.
+/// Rng is used for generating the handshake key and the frame masks.
+pub struct WebSocketConnection<T, R>
+where
+    T: Read + Write,
+    R: RngCore,
+{
+    io: T,
+    rng: R,
+    // Bytes of the current data frame which were not read yet
+    remaining: u64,
+    closed: bool,
+}
+
+impl<T, R> WebSocketConnection<T, R>
+where
+    T: Read + Write,
+    R: RngCore,
+{
+    pub fn new(io: T, rng: R) -> Self {
+        Self {
+            io,
+            rng,
+            remaining: 0,
+            closed: false,
+        }
+    }
+
+    /// Returns the wrapped byte stream.
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+
+    /// Performs the opening handshake. Method sends the HTTP upgrade request for the `path`
+    /// on the `host` and waits for the `101 Switching Protocols` response. Response has to
+    /// contain `Sec-WebSocket-Accept` matching the sent key and select the `mqtt` subprotocol,
+    /// otherwise `Handshake` error is returned.
+    pub async fn connect(
+        &mut self,
+        host: &str,
+        path: &str,
+    ) -> Result<(), WebSocketError<T::Error>> {
+        let mut key = [0u8; 16];
+        self.rng.fill_bytes(&mut key);
+        let mut encoded_key = [0u8; 24];
+        base64_encode(&key, &mut encoded_key);
+
+        trace!("Sending WebSocket upgrade request");
+        self.write_raw(b"GET ").await?;
+        self.write_raw(path.as_bytes()).await?;
+        self.write_raw(b" HTTP/1.1\r\nHost: ").await?;
+        self.write_raw(host.as_bytes()).await?;
+        self.write_raw(b"\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: ")
+            .await?;
+        self.write_raw(&encoded_key).await?;
+        self.write_raw(b"\r\nSec-WebSocket-Version: 13\r\nSec-WebSocket-Protocol: mqtt\r\n\r\n")
+            .await?;
+        self.io.flush().await.map_err(WebSocketError::Io)?;
+
+        // Response is read byte by byte so no frame data following the header are consumed
+        let mut response = [0u8; MAX_HANDSHAKE_RESPONSE];
+        let mut len = 0;
+        loop {
+            if len == MAX_HANDSHAKE_RESPONSE {
+                error!("WebSocket handshake response is too long!");
+                return Err(WebSocketError::Handshake);
+            }
+            self.io.read_exact(&mut response[len..len + 1]).await?;
+            len += 1;
+            if response[..len].ends_with(b"\r\n\r\n") {
+                break;
+            }
+        }
+
+        if !response.starts_with(b"HTTP/1.1 101") {
+            error!("Server refused upgrade to WebSocket protocol!");
+            return Err(WebSocketError::Handshake);
+        }
+
+        let mut expected_accept = [0u8; 28];
+        base64_encode(&accept_digest(&encoded_key), &mut expected_accept);
+        let mut accepted = false;
+        let mut protocol = false;
+        // Status line is skipped, header names are case insensitive
+        for line in response[..len - 4].split(|b| *b == b'\n').skip(1) {
+            let line = line.strip_suffix(b"\r").unwrap_or(line);
+            let Some(colon) = line.iter().position(|b| *b == b':') else {
+                continue;
+            };
+            let name = &line[..colon];
+            let value = trim_whitespace(&line[colon + 1..]);
+            if name.eq_ignore_ascii_case(b"Sec-WebSocket-Accept") {
+                accepted = value == expected_accept;
+            } else if name.eq_ignore_ascii_case(b"Sec-WebSocket-Protocol") {
+                protocol = value == b"mqtt";
+            }
+        }
+        if !accepted {
+            error!("Server sent invalid Sec-WebSocket-Accept!");
+            return Err(WebSocketError::Handshake);
+        }
+        if !protocol {
+            error!("Server did not select the mqtt subprotocol!");
+            return Err(WebSocketError::Handshake);
+        }
+        Ok(())
+    }
+
+    async fn write_raw(&mut self, buf: &[u8]) -> Result<(), WebSocketError<T::Error>> {
+        self.io.write_all(buf).await.map_err(WebSocketError::Io)
+    }
+
+    /// Writes a single final masked frame with the `payload`.
+    async fn write_frame(
+        &mut self,
+        opcode: u8,
+        payload: &[u8],
+    ) -> Result<(), WebSocketError<T::Error>> {
+        let mut header = [0u8; 14];
+        header[0] = 0x80 | opcode;
+        let mut header_len = 2;
+        let len = payload.len();
+        if len < 126 {
+            header[1] = 0x80 | len as u8;
+        } else if len <= u16::MAX as usize {
+            header[1] = 0x80 | 126;
+            header[2..4].copy_from_slice(&(len as u16).to_be_bytes());
+            header_len = 4;
+        } else {
+            header[1] = 0x80 | 127;
+            header[2..10].copy_from_slice(&(len as u64).to_be_bytes());
+            header_len = 10;
+        }
+        let mut mask = [0u8; 4];
+        self.rng.fill_bytes(&mut mask);
+        header[header_len..header_len + 4].copy_from_slice(&mask);
+        header_len += 4;
+        self.write_raw(&header[..header_len]).await?;
+
+        let mut chunk = [0u8; 64];
+        let mut offset = 0;
+        while offset < len {
+            let size = core::cmp::min(chunk.len(), len - offset);
+            for i in 0..size {
+                chunk[i] = payload[offset + i] ^ mask[(offset + i) % 4];
+            }
+            self.write_raw(&chunk[..size]).await?;
+            offset += size;
+        }
+        Ok(())
+    }
+
+    /// Reads frame headers until a data frame with a payload is found. Control frames are
+    /// handled on the way. Returns `false` if the server closed the connection.
+    async fn next_data_frame(&mut self) -> Result<bool, WebSocketError<T::Error>> {
+        loop {
+            let mut header = [0u8; 2];
+            self.io.read_exact(&mut header).await?;
+            let opcode = header[0] & 0x0F;
+            // No extension is negotiated, so the reserved bits have to be 0
+            if header[0] & 0x70 != 0 {
+                error!("WebSocket frame has reserved bits set!");
+                return Err(WebSocketError::Protocol);
+            }
+            // Server must not mask the frames it sends
+            if header[1] & 0x80 != 0 {
+                error!("WebSocket frame sent by the server is masked!");
+                return Err(WebSocketError::Protocol);
+            }
+            let mut len = (header[1] & 0x7F) as u64;
+            if len == 126 {
+                let mut ext = [0u8; 2];
+                self.io.read_exact(&mut ext).await?;
+                len = u16::from_be_bytes(ext) as u64;
+            } else if len == 127 {
+                let mut ext = [0u8; 8];
+                self.io.read_exact(&mut ext).await?;
+                len = u64::from_be_bytes(ext);
+            }
+            match opcode {
+                OPCODE_TEXT => {
+                    // MQTT v5 section 6.0: MQTT data has to be sent in binary frames
+                    error!("WebSocket text frame received, closing connection!");
+                    self.closed = true;
+                    self.write_frame(OPCODE_CLOSE, &CLOSE_PROTOCOL_ERROR.to_be_bytes())
+                        .await?;
+                    self.io.flush().await.map_err(WebSocketError::Io)?;
+                    return Err(WebSocketError::Protocol);
+                }
+                OPCODE_CONTINUATION | OPCODE_BINARY => {
+                    if len == 0 {
+                        continue;
+                    }
+                    self.remaining = len;
+                    return Ok(true);
+                }
+                OPCODE_CLOSE | OPCODE_PING | OPCODE_PONG => {
+                    if len > MAX_CONTROL_PAYLOAD as u64 {
+                        error!("WebSocket control frame is too long!");
+                        return Err(WebSocketError::Protocol);
+                    }
+                    let mut payload = [0u8; MAX_CONTROL_PAYLOAD];
+                    let payload = &mut payload[..len as usize];
+                    self.io.read_exact(payload).await?;
+                    if opcode == OPCODE_PING {
+                        trace!("Answering WebSocket ping");
+                        self.write_frame(OPCODE_PONG, payload).await?;
+                        self.io.flush().await.map_err(WebSocketError::Io)?;
+                    } else if opcode == OPCODE_CLOSE {
+                        trace!("Server closed WebSocket connection");
+                        self.closed = true;
+                        // Close is answered with the status code sent by the server
+                        let status = &payload[..core::cmp::min(payload.len(), 2)];
+                        self.write_frame(OPCODE_CLOSE, status).await?;
+                        self.io.flush().await.map_err(WebSocketError::Io)?;
+                        return Ok(false);
+                    }
+                }
+                _ => {
+                    error!("Unknown WebSocket opcode!");
+                    return Err(WebSocketError::Protocol);
+                }
+            }
+        }
+    }
+}
+
+impl<T, R> ErrorType for WebSocketConnection<T, R>
+where
+    T: Read + Write,
+    R: RngCore,
+{
+    type Error = WebSocketError<T::Error>;
+}
+
+impl<T, R> Read for WebSocketConnection<T, R>
+where
+    T: Read + Write,
+    R: RngCore,
+{
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() || self.closed {
+            return Ok(0);
+        }
+        if self.remaining == 0 && !self.next_data_frame().await? {
+            return Ok(0);
+        }
+        let len = core::cmp::min(buf.len() as u64, self.remaining) as usize;
+        let read = self
+            .io
+            .read(&mut buf[..len])
+            .await
+            .map_err(WebSocketError::Io)?;
+        if read == 0 {
+            return Err(WebSocketError::ConnectionClosed);
+        }
+        self.remaining -= read as u64;
+        Ok(read)
+    }
+}
+
+impl<T, R> Write for WebSocketConnection<T, R>
+where
+    T: Read + Write,
+    R: RngCore,
+{
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.write_frame(OPCODE_BINARY, buf).await?;
+        Ok(buf.len())
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.io.flush().await.map_err(WebSocketError::Io)
+    }
+}
+
+/// Encodes the `input` into base64 with padding. Output has to be large enough
+/// to hold the encoded data.
+fn base64_encode(input: &[u8], output: &mut [u8]) {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    for (i, chunk) in input.chunks(3).enumerate() {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let out = &mut output[i * 4..i * 4 + 4];
+        out[0] = ALPHABET[(b[0] >> 2) as usize];
+        out[1] = ALPHABET[(((b[0] & 0x03) << 4) | (b[1] >> 4)) as usize];
+        out[2] = if chunk.len() > 1 {
+            ALPHABET[(((b[1] & 0x0F) << 2) | (b[2] >> 6)) as usize]
+        } else {
+            b'='
+        };
+        out[3] = if chunk.len() > 2 {
+            ALPHABET[(b[2] & 0x3F) as usize]
+        } else {
+            b'='
+        };
+    }
+}
+
+/// Returns the `value` without leading and trailing spaces and tabs.
+fn trim_whitespace(mut value: &[u8]) -> &[u8] {
+    while let [b' ' | b'\t', rest @ ..] = value {
+        value = rest;
+    }
+    while let [rest @ .., b' ' | b'\t'] = value {
+        value = rest;
+    }
+    value
+}
+
+/// Returns SHA-1 digest of the `key` followed by the `ACCEPT_GUID`, which is encoded
+/// into the expected `Sec-WebSocket-Accept` value.
+fn accept_digest(key: &[u8; 24]) -> [u8; 20] {
+    let mut message = [0u8; 24 + ACCEPT_GUID.len()];
+    message[..24].copy_from_slice(key);
+    message[24..].copy_from_slice(ACCEPT_GUID);
+    sha1(&message)
+}
+
+/// Computes SHA-1 digest of the `input` as defined in RFC 3174.
+fn sha1(input: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let bit_len = (input.len() as u64) * 8;
+    // Message is followed by 0x80, zero padding and the length in bits
+    let blocks = (input.len() + 8) / 64 + 1;
+    for block_index in 0..blocks {
+        let mut block = [0u8; 64];
+        for (i, byte) in block.iter_mut().enumerate() {
+            let pos = block_index * 64 + i;
+            *byte = match pos.cmp(&input.len()) {
+                core::cmp::Ordering::Less => input[pos],
+                core::cmp::Ordering::Equal => 0x80,
+                core::cmp::Ordering::Greater => 0,
+            };
+        }
+        if block_index == blocks - 1 {
+            block[56..].copy_from_slice(&bit_len.to_be_bytes());
+        }
+
+        let mut w = [0u32; 80];
+        for i in 0..16 {
+            w[i] = u32::from_be_bytes([
+                block[i * 4],
+                block[i * 4 + 1],
+                block[i * 4 + 2],
+                block[i * 4 + 3],
+            ]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        h[0] = h[0].wrapping_add(a);
+        h[1] = h[1].wrapping_add(b);
+        h[2] = h[2].wrapping_add(c);
+        h[3] = h[3].wrapping_add(d);
+        h[4] = h[4].wrapping_add(e);
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, word) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
@@ -22,5 +22,7 @@
  * SOFTWARE.
  */
 
+#[allow(unused_must_use)]
+pub mod v3;
 #[allow(unused_must_use)]
 pub mod v5;
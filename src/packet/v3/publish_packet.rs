@@ -0,0 +1,167 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::encoding::variable_byte_integer::VariableByteIntegerEncoder;
+use crate::packet::v5::mqtt_packet::Packet;
+use crate::packet::v5::packet_type::PacketType;
+use crate::packet::v5::property::Property;
+use crate::packet::v5::publish_packet::QualityOfService;
+use crate::utils::buffer_reader::BuffReader;
+use crate::utils::buffer_writer::BuffWriter;
+use crate::utils::types::{BufferError, EncodedString};
+
+/// MQTTv3.1.1 `PUBLISH` packet, the payload directly follows the packet identifier.
+pub struct PublishPacket<'a> {
+    pub fixed_header: u8,
+    pub remain_len: u32,
+    pub topic_name: EncodedString<'a>,
+    pub packet_identifier: u16,
+    pub message: Option<&'a [u8]>,
+}
+
+impl<'a> PublishPacket<'a> {
+    pub fn add_topic_name(&mut self, topic_name: &'a str) {
+        self.topic_name.string = topic_name;
+        self.topic_name.len = topic_name.len() as u16;
+    }
+
+    pub fn add_message(&mut self, message: &'a [u8]) {
+        self.message = Some(message);
+    }
+
+    pub fn add_qos(&mut self, qos: QualityOfService) {
+        self.fixed_header |= <QualityOfService as Into<u8>>::into(qos);
+    }
+
+    pub fn add_retain(&mut self, retain: bool) {
+        self.fixed_header |= retain as u8
+    }
+
+    pub fn add_identifier(&mut self, identifier: u16) {
+        self.packet_identifier = identifier;
+    }
+
+    /// Returns the remaining length of the packet, which is the length of the packet
+    /// without the fixed header and the remaining length itself.
+    pub fn remaining_len(&self) -> Result<u32, BufferError> {
+        self.remaining_len_with_message_len(self.message.map_or(0, |message| message.len()))
+    }
+
+    /// Returns the number of bytes the whole packet takes when encoded, including
+    /// the topic and the message.
+    pub fn encoded_len(&self) -> Result<usize, BufferError> {
+        self.encoded_len_with_message_len(self.message.map_or(0, |message| message.len()))
+    }
+
+    /// Returns the number of bytes the packet takes when encoded with a message of
+    /// `message_len` bytes instead of the message currently set.
+    pub fn encoded_len_with_message_len(&self, message_len: usize) -> Result<usize, BufferError> {
+        let rm_ln = self.remaining_len_with_message_len(message_len)?;
+        let rm_ln_len = VariableByteIntegerEncoder::len(VariableByteIntegerEncoder::encode(rm_ln)?);
+        Ok(1 + rm_ln_len + rm_ln as usize)
+    }
+
+    fn remaining_len_with_message_len(&self, message_len: usize) -> Result<u32, BufferError> {
+        let mut rm_ln = message_len + self.topic_name.len as usize + 2;
+        if self.fixed_header & 0x06 != 0 {
+            rm_ln += 2;
+        }
+        u32::try_from(rm_ln).map_err(|_| BufferError::VariableByteIntegerError)
+    }
+}
+
+impl<'a> Packet<'a> for PublishPacket<'a> {
+    fn new() -> Self {
+        Self {
+            fixed_header: PacketType::Publish.into(),
+            remain_len: 0,
+            topic_name: EncodedString::new(),
+            packet_identifier: 1,
+            message: None,
+        }
+    }
+
+    fn encode(&mut self, buffer: &mut [u8], buffer_len: usize) -> Result<usize, BufferError> {
+        let mut buff_writer = BuffWriter::new(buffer, buffer_len);
+
+        let rm_ln = self.remaining_len()?;
+        let msg_len = self.message.unwrap().len() as u32;
+
+        buff_writer.write_u8(self.fixed_header)?;
+        let qos = self.fixed_header & 0x06;
+
+        buff_writer.write_variable_byte_int(rm_ln)?;
+        buff_writer.write_string_ref(&self.topic_name)?;
+
+        if qos != 0 {
+            buff_writer.write_u16(self.packet_identifier)?;
+        }
+
+        buff_writer.insert_ref(msg_len as usize, self.message.unwrap())?;
+        Ok(buff_writer.position)
+    }
+
+    fn decode(&mut self, buff_reader: &mut BuffReader<'a>) -> Result<(), BufferError> {
+        if self.decode_fixed_header(buff_reader)? != PacketType::Publish {
+            error!("Packet you are trying to decode is not PUBLISH packet!");
+            return Err(BufferError::PacketTypeMismatch);
+        }
+        self.topic_name = buff_reader.read_string()?;
+        let qos = self.fixed_header & 0x06;
+        if qos != 0 {
+            // Decode only for QoS 1 / 2
+            self.packet_identifier = buff_reader.read_u16()?;
+        }
+        let mut total_len =
+            VariableByteIntegerEncoder::len(VariableByteIntegerEncoder::encode(self.remain_len)?);
+        total_len = total_len + 1 + self.remain_len as usize;
+        self.message = Some(buff_reader.read_message(total_len));
+        Ok(())
+    }
+
+    fn set_property_len(&mut self, _value: u32) {
+        error!("MQTTv3 PUBLISH packet does not contain any properties!");
+    }
+
+    fn get_property_len(&mut self) -> u32 {
+        error!("MQTTv3 PUBLISH packet does not contain any properties!");
+        0
+    }
+
+    fn push_to_properties(&mut self, _property: Property<'a>) {
+        error!("MQTTv3 PUBLISH packet does not contain any properties!");
+    }
+
+    fn property_allowed(&mut self, _property: &Property<'a>) -> bool {
+        false
+    }
+
+    fn set_fixed_header(&mut self, header: u8) {
+        self.fixed_header = header;
+    }
+
+    fn set_remaining_len(&mut self, remaining_len: u32) {
+        self.remain_len = remaining_len;
+    }
+}
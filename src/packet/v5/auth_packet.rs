@@ -59,6 +59,38 @@ impl<'a, const MAX_PROPERTIES: usize> AuthPacket<'a, MAX_PROPERTIES> {
             error!("Provided property is not correct AUTH packet property!");
         }
     }
+
+    /// Returns the authentication method of the packet.
+    pub fn authentication_method(&self) -> Option<&'a str> {
+        self.properties.iter().find_map(|prop| match prop {
+            Property::AuthenticationMethod(method) => Some(method.string),
+            _ => None,
+        })
+    }
+
+    /// Returns the authentication data of the packet.
+    pub fn authentication_data(&self) -> Option<&'a [u8]> {
+        self.properties.iter().find_map(|prop| match prop {
+            Property::AuthenticationData(data) => Some(data.bin),
+            _ => None,
+        })
+    }
+
+    /// Returns the human readable reason string of the packet.
+    pub fn reason_string(&self) -> Option<&'a str> {
+        self.properties.iter().find_map(|prop| match prop {
+            Property::ReasonString(reason) => Some(reason.string),
+            _ => None,
+        })
+    }
+
+    /// Returns name and value of all user properties of the packet.
+    pub fn user_properties(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.properties.iter().filter_map(|prop| match prop {
+            Property::UserProperty(pair) => Some((pair.name.string, pair.value.string)),
+            _ => None,
+        })
+    }
 }
 
 impl<'a, const MAX_PROPERTIES: usize> Packet<'a> for AuthPacket<'a, MAX_PROPERTIES> {
@@ -90,8 +122,20 @@ impl<'a, const MAX_PROPERTIES: usize> Packet<'a> for AuthPacket<'a, MAX_PROPERTI
     }
 
     fn decode(&mut self, buff_reader: &mut BuffReader<'a>) -> Result<(), BufferError> {
-        self.decode_fixed_header(buff_reader)?;
+        if self.decode_fixed_header(buff_reader)? != PacketType::Auth {
+            error!("Packet you are trying to decode is not AUTH packet!");
+            return Err(BufferError::PacketTypeMismatch);
+        }
+        // Reason code and properties can be omitted if the reason is Success
+        if self.remain_len == 0 {
+            self.auth_reason = 0x00;
+            return Ok(());
+        }
         self.auth_reason = buff_reader.read_u8()?;
+        if self.remain_len == 1 {
+            self.property_len = 0;
+            return Ok(());
+        }
         self.decode_properties(buff_reader)
     }
 
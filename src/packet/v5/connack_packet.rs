@@ -42,7 +42,49 @@ pub struct ConnackPacket<'a, const MAX_PROPERTIES: usize> {
     pub properties: Vec<Property<'a>, MAX_PROPERTIES>,
 }
 
-impl<'a, const MAX_PROPERTIES: usize> ConnackPacket<'a, MAX_PROPERTIES> {}
+impl<'a, const MAX_PROPERTIES: usize> ConnackPacket<'a, MAX_PROPERTIES> {
+    /// Returns the client identifier assigned by the broker if the client
+    /// connected with an empty client identifier.
+    pub fn assigned_client_identifier(&self) -> Option<&'a str> {
+        self.properties.iter().find_map(|prop| match prop {
+            Property::AssignedClientIdentifier(id) => Some(id.string),
+            _ => None,
+        })
+    }
+
+    /// Returns the response information if the client requested it and the broker sent it.
+    pub fn response_information(&self) -> Option<&'a str> {
+        self.properties.iter().find_map(|prop| match prop {
+            Property::ResponseInformation(info) => Some(info.string),
+            _ => None,
+        })
+    }
+
+    /// Returns the authentication method if the broker sent it.
+    pub fn authentication_method(&self) -> Option<&'a str> {
+        self.properties.iter().find_map(|prop| match prop {
+            Property::AuthenticationMethod(method) => Some(method.string),
+            _ => None,
+        })
+    }
+
+    /// Returns the authentication data if the broker sent it. In the enhanced
+    /// authentication this is the last authentication data of the broker.
+    pub fn authentication_data(&self) -> Option<&'a [u8]> {
+        self.properties.iter().find_map(|prop| match prop {
+            Property::AuthenticationData(data) => Some(data.bin),
+            _ => None,
+        })
+    }
+
+    /// Returns name and value of all user properties sent by the broker.
+    pub fn user_properties(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.properties.iter().filter_map(|prop| match prop {
+            Property::UserProperty(pair) => Some((pair.name.string, pair.value.string)),
+            _ => None,
+        })
+    }
+}
 
 impl<'a, const MAX_PROPERTIES: usize> Packet<'a> for ConnackPacket<'a, MAX_PROPERTIES> {
     fn new() -> Self {
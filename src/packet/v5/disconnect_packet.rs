@@ -47,9 +47,26 @@ pub struct DisconnectPacket<'a, const MAX_PROPERTIES: usize> {
 }
 
 impl<'a, const MAX_PROPERTIES: usize> DisconnectPacket<'a, MAX_PROPERTIES> {
-    fn add_reason(&mut self, reason: u8) {
+    pub fn add_reason(&mut self, reason: u8) {
         self.disconnect_reason = reason;
     }
+
+    /// Returns the server which should be used instead, if the broker sent it
+    /// together with `ServerMoved` or `UseAnotherServer` reason.
+    pub fn server_reference(&self) -> Option<&'a str> {
+        self.properties.iter().find_map(|prop| match prop {
+            Property::ServerReference(reference) => Some(reference.string),
+            _ => None,
+        })
+    }
+
+    /// Returns name and value of all user properties sent by the broker.
+    pub fn user_properties(&self) -> impl Iterator<Item = (&'a str, &'a str)> + '_ {
+        self.properties.iter().filter_map(|prop| match prop {
+            Property::UserProperty(pair) => Some((pair.name.string, pair.value.string)),
+            _ => None,
+        })
+    }
 }
 
 impl<'a, const MAX_PROPERTIES: usize> Packet<'a> for DisconnectPacket<'a, MAX_PROPERTIES> {
@@ -87,6 +104,11 @@ impl<'a, const MAX_PROPERTIES: usize> Packet<'a> for DisconnectPacket<'a, MAX_PR
             return Ok(());
         }
         self.disconnect_reason = buff_reader.read_u8()?;
+        // Property length can be omitted if there are no properties
+        if self.remain_len == 1 {
+            self.property_len = 0;
+            return Ok(());
+        }
         self.decode_properties(buff_reader)
     }
 
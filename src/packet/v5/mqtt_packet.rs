@@ -100,3 +100,20 @@ pub trait Packet<'a> {
         Ok(PacketType::from(first_byte))
     }
 }
+
+/// Encodes packet into the provided buffer without the network and returns
+/// the number of written bytes.
+pub fn encode_to_slice<'a, P: Packet<'a>>(
+    packet: &mut P,
+    buffer: &mut [u8],
+) -> Result<usize, BufferError> {
+    let len = buffer.len();
+    packet.encode(buffer, len)
+}
+
+/// Decodes packet of type `P` from the provided buffer without the network.
+pub fn decode_from_slice<'a, P: Packet<'a>>(buffer: &'a [u8]) -> Result<P, BufferError> {
+    let mut packet = P::new();
+    packet.decode(&mut BuffReader::new(buffer, buffer.len()))?;
+    Ok(packet)
+}
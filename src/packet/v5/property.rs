@@ -302,9 +302,13 @@ impl<'a> Property<'a> {
             Ok(0x03) => Ok(Property::ContentType(buff_reader.read_string()?)),
             Ok(0x08) => Ok(Property::ResponseTopic(buff_reader.read_string()?)),
             Ok(0x09) => Ok(Property::CorrelationData(buff_reader.read_binary()?)),
-            Ok(0x0B) => Ok(Property::SubscriptionIdentifier(
-                buff_reader.read_variable_byte_int()?,
-            )),
+            Ok(0x0B) => match buff_reader.read_variable_byte_int()? {
+                0 => {
+                    error!("Subscription identifier 0 is not valid!");
+                    Err(BufferError::ProtocolError)
+                }
+                id => Ok(Property::SubscriptionIdentifier(id)),
+            },
             Ok(0x11) => Ok(Property::SessionExpiryInterval(buff_reader.read_u32()?)),
             Ok(0x12) => Ok(Property::AssignedClientIdentifier(
                 buff_reader.read_string()?,
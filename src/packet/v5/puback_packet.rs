@@ -33,6 +33,9 @@ use crate::utils::types::BufferError;
 use super::packet_type::PacketType;
 use super::property::Property;
 
+/// PUBACK packet acknowledging the QoS 1 PUBLISH. Reason code below 0x80, e.g. `Success` or
+/// `NoMatchingSubscribers`, completes the publish and the message must not be sent again.
+/// Reason code 0x80 or above means the broker rejected the message.
 pub struct PubackPacket<'a, const MAX_PROPERTIES: usize> {
     pub fixed_header: u8,
     pub remain_len: u32,
@@ -22,6 +22,8 @@
  * SOFTWARE.
  */
 
+use core::str::Utf8Error;
+
 use heapless::Vec;
 
 use crate::encoding::variable_byte_integer::VariableByteIntegerEncoder;
@@ -35,6 +37,7 @@ use super::packet_type::PacketType;
 use super::property::Property;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum QualityOfService {
     QoS0,
     QoS1,
@@ -95,6 +98,63 @@ impl<'a, const MAX_PROPERTIES: usize> PublishPacket<'a, MAX_PROPERTIES> {
     pub fn add_identifier(&mut self, identifier: u16) {
         self.packet_identifier = identifier;
     }
+
+    /// Returns all subscription identifiers of the packet. Broker sends more identifiers
+    /// if the message matched more subscriptions of the client.
+    pub fn subscription_identifiers(&self) -> impl Iterator<Item = u32> + '_ {
+        self.properties.iter().filter_map(|prop| match prop {
+            Property::SubscriptionIdentifier(id) => Some(*id),
+            _ => None,
+        })
+    }
+
+    /// Returns the remaining length of the packet, which is the length of the packet
+    /// without the fixed header and the remaining length itself.
+    pub fn remaining_len(&self) -> Result<u32, BufferError> {
+        self.remaining_len_with_message_len(self.message.map_or(0, |message| message.len()))
+    }
+
+    /// Returns the number of bytes the whole packet takes when encoded, including
+    /// the topic, the properties and the message.
+    pub fn encoded_len(&self) -> Result<usize, BufferError> {
+        self.encoded_len_with_message_len(self.message.map_or(0, |message| message.len()))
+    }
+
+    /// Returns the number of bytes the packet takes when encoded with a message of
+    /// `message_len` bytes instead of the message currently set.
+    pub fn encoded_len_with_message_len(&self, message_len: usize) -> Result<usize, BufferError> {
+        let rm_ln = self.remaining_len_with_message_len(message_len)?;
+        let rm_ln_len = VariableByteIntegerEncoder::len(VariableByteIntegerEncoder::encode(rm_ln)?);
+        Ok(1 + rm_ln_len + rm_ln as usize)
+    }
+
+    fn remaining_len_with_message_len(&self, message_len: usize) -> Result<u32, BufferError> {
+        let property_len_enc: [u8; 4] = VariableByteIntegerEncoder::encode(self.property_len)?;
+        let property_len_len = VariableByteIntegerEncoder::len(property_len_enc);
+        let mut rm_ln = self.property_len as usize
+            + property_len_len
+            + message_len
+            + self.topic_name.len as usize
+            + 2;
+        if self.fixed_header & 0x06 != 0 {
+            rm_ln += 2;
+        }
+        u32::try_from(rm_ln).map_err(|_| BufferError::VariableByteIntegerError)
+    }
+
+    /// Returns the message as a string if the Payload Format Indicator of the packet
+    /// says that the message is UTF-8 encoded. The message is validated on every call.
+    /// Returns `None` if the message is declared as unspecified bytes or missing.
+    pub fn message_str(&self) -> Option<Result<&'a str, Utf8Error>> {
+        let utf8 = self
+            .properties
+            .iter()
+            .any(|prop| matches!(prop, Property::PayloadFormat(1)));
+        if !utf8 {
+            return None;
+        }
+        self.message.map(core::str::from_utf8)
+    }
 }
 
 impl<'a, const MAX_PROPERTIES: usize> Packet<'a> for PublishPacket<'a, MAX_PROPERTIES> {
@@ -113,17 +173,11 @@ impl<'a, const MAX_PROPERTIES: usize> Packet<'a> for PublishPacket<'a, MAX_PROPE
     fn encode(&mut self, buffer: &mut [u8], buffer_len: usize) -> Result<usize, BufferError> {
         let mut buff_writer = BuffWriter::new(buffer, buffer_len);
 
-        let mut rm_ln = self.property_len;
-        let property_len_enc: [u8; 4] = VariableByteIntegerEncoder::encode(self.property_len)?;
-        let property_len_len = VariableByteIntegerEncoder::len(property_len_enc);
+        let rm_ln = self.remaining_len()?;
         let msg_len = self.message.unwrap().len() as u32;
-        rm_ln = rm_ln + property_len_len as u32 + msg_len + self.topic_name.len as u32 + 2;
 
         buff_writer.write_u8(self.fixed_header)?;
         let qos = self.fixed_header & 0x06;
-        if qos != 0 {
-            rm_ln += 2;
-        }
 
         buff_writer.write_variable_byte_int(rm_ln)?;
         buff_writer.write_string_ref(&self.topic_name)?;
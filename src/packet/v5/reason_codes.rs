@@ -24,7 +24,9 @@
 
 use core::fmt::{Display, Formatter};
 
-#[derive(Debug, PartialEq)]
+use crate::packet::v5::publish_packet::QualityOfService;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ReasonCode {
     Success,
@@ -70,11 +72,159 @@ pub enum ReasonCode {
     MaximumConnectTime,
     SubscriptionIdentifiersNotSupported,
     WildcardSubscriptionNotSupported,
+    /// Value passed to the client API is not valid, nothing was sent to the broker.
+    InvalidArgument,
     TimerNotSupported,
     BuffError,
     NetworkError,
 }
 
+/// Classification of the `ReasonCode` by its meaning for the client.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReasonCodeCategory {
+    /// Operation completed as requested.
+    Success,
+    /// Operation completed, but the broker reports something worth noticing
+    /// (e.g. there was no subscriber for the published message).
+    Warning,
+    /// Failure caused by the client, its packets or its local state.
+    ClientError,
+    /// Failure caused by the state, limits or capabilities of the broker.
+    ServerError,
+    /// The network connection failed, reconnecting may help.
+    Transport,
+}
+
+impl ReasonCode {
+    /// Returns the category of the reason code.
+    pub fn category(&self) -> ReasonCodeCategory {
+        match self {
+            ReasonCode::Success
+            | ReasonCode::GrantedQoS1
+            | ReasonCode::GrantedQoS2
+            | ReasonCode::DisconnectWithWillMessage
+            | ReasonCode::ContinueAuth
+            | ReasonCode::ReAuthenticate => ReasonCodeCategory::Success,
+            ReasonCode::NoMatchingSubscribers | ReasonCode::NoSubscriptionExisted => {
+                ReasonCodeCategory::Warning
+            }
+            ReasonCode::UnspecifiedError
+            | ReasonCode::ImplementationSpecificError
+            | ReasonCode::ServerUnavailable
+            | ReasonCode::ServerBusy
+            | ReasonCode::ServerShuttingDown
+            | ReasonCode::SessionTakeOver
+            | ReasonCode::QuotaExceeded
+            | ReasonCode::AdministrativeAction
+            | ReasonCode::RetainNotSupported
+            | ReasonCode::QoSNotSupported
+            | ReasonCode::UseAnotherServer
+            | ReasonCode::ServerMoved
+            | ReasonCode::SharedSubscriptionNotSupported
+            | ReasonCode::ConnectionRateExceeded
+            | ReasonCode::MaximumConnectTime
+            | ReasonCode::SubscriptionIdentifiersNotSupported
+            | ReasonCode::WildcardSubscriptionNotSupported => ReasonCodeCategory::ServerError,
+            ReasonCode::MalformedPacket
+            | ReasonCode::ProtocolError
+            | ReasonCode::UnsupportedProtocolVersion
+            | ReasonCode::ClientIdNotValid
+            | ReasonCode::BadUserNameOrPassword
+            | ReasonCode::NotAuthorized
+            | ReasonCode::Banned
+            | ReasonCode::BadAuthMethod
+            | ReasonCode::KeepAliveTimeout
+            | ReasonCode::TopicFilterInvalid
+            | ReasonCode::TopicNameInvalid
+            | ReasonCode::PacketIdentifierInUse
+            | ReasonCode::PacketIdentifierNotFound
+            | ReasonCode::ReceiveMaximumExceeded
+            | ReasonCode::TopicAliasInvalid
+            | ReasonCode::PacketTooLarge
+            | ReasonCode::MessageRateTooHigh
+            | ReasonCode::PayloadFormatInvalid
+            | ReasonCode::InvalidArgument
+            | ReasonCode::TimerNotSupported
+            | ReasonCode::BuffError => ReasonCodeCategory::ClientError,
+            ReasonCode::NetworkError => ReasonCodeCategory::Transport,
+        }
+    }
+
+    /// Returns true if the operation completed as requested.
+    pub fn is_success(&self) -> bool {
+        self.category() == ReasonCodeCategory::Success
+    }
+
+    /// Returns true if the operation completed, even if the broker
+    /// attached a warning (`NoMatchingSubscribers`, `NoSubscriptionExisted`).
+    pub fn is_normal(&self) -> bool {
+        matches!(
+            self.category(),
+            ReasonCodeCategory::Success | ReasonCodeCategory::Warning
+        )
+    }
+
+    /// Returns true if the operation failed.
+    pub fn is_error(&self) -> bool {
+        !self.is_normal()
+    }
+
+    /// Returns true if the broker asks the client to connect to another server.
+    /// The server is usually provided in the Server Reference property.
+    pub fn is_redirect(&self) -> bool {
+        matches!(self, ReasonCode::UseAnotherServer | ReasonCode::ServerMoved)
+    }
+
+    /// Returns the QoS granted by the broker if the reason code comes from SUBACK and
+    /// the subscription was accepted. Returns `None` for the failure reason codes.
+    pub fn granted_qos(&self) -> Option<QualityOfService> {
+        match self {
+            ReasonCode::Success => Some(QualityOfService::QoS0),
+            ReasonCode::GrantedQoS1 => Some(QualityOfService::QoS1),
+            ReasonCode::GrantedQoS2 => Some(QualityOfService::QoS2),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the rejected operation, e.g. a publish refused in PUBACK, can succeed
+    /// when it is retried later. Errors caused by the content of the request or by missing
+    /// permissions, like `NotAuthorized` or `TopicNameInvalid`, are permanent.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ReasonCode::ImplementationSpecificError
+                | ReasonCode::ServerBusy
+                | ReasonCode::PacketIdentifierInUse
+                | ReasonCode::ReceiveMaximumExceeded
+                | ReasonCode::MessageRateTooHigh
+                | ReasonCode::QuotaExceeded
+        )
+    }
+
+    /// Returns true if the client is allowed to send the reason code in the DISCONNECT packet.
+    /// Other reason codes can be sent only by the broker or are not valid in DISCONNECT.
+    pub fn allowed_in_client_disconnect(&self) -> bool {
+        matches!(
+            self,
+            ReasonCode::Success
+                | ReasonCode::DisconnectWithWillMessage
+                | ReasonCode::UnspecifiedError
+                | ReasonCode::MalformedPacket
+                | ReasonCode::ProtocolError
+                | ReasonCode::ImplementationSpecificError
+                | ReasonCode::TopicNameInvalid
+                | ReasonCode::ReceiveMaximumExceeded
+                | ReasonCode::TopicAliasInvalid
+                | ReasonCode::PacketTooLarge
+                | ReasonCode::MessageRateTooHigh
+                | ReasonCode::QuotaExceeded
+                | ReasonCode::AdministrativeAction
+                | ReasonCode::PayloadFormatInvalid
+        )
+    }
+}
+
 impl From<ReasonCode> for u8 {
     fn from(value: ReasonCode) -> Self {
         match value {
@@ -121,6 +271,7 @@ impl From<ReasonCode> for u8 {
             ReasonCode::MaximumConnectTime => 0xA0,
             ReasonCode::SubscriptionIdentifiersNotSupported => 0xA1,
             ReasonCode::WildcardSubscriptionNotSupported => 0xA2,
+            ReasonCode::InvalidArgument => 0xFC,
             ReasonCode::TimerNotSupported => 0xFD,
             ReasonCode::BuffError => 0xFE,
             ReasonCode::NetworkError => 0xFF,
@@ -170,9 +321,11 @@ impl From<u8> for ReasonCode {
             0x9C => ReasonCode::UseAnotherServer,
             0x9D => ReasonCode::ServerMoved,
             0x9E => ReasonCode::SharedSubscriptionNotSupported,
+            0x9F => ReasonCode::ConnectionRateExceeded,
             0xA0 => ReasonCode::MaximumConnectTime,
             0xA1 => ReasonCode::SubscriptionIdentifiersNotSupported,
             0xA2 => ReasonCode::WildcardSubscriptionNotSupported,
+            0xFC => ReasonCode::InvalidArgument,
             0xFD => ReasonCode::TimerNotSupported,
             0xFE => ReasonCode::BuffError,
             _ => ReasonCode::NetworkError,
@@ -234,9 +387,13 @@ impl Display for ReasonCode {
             ReasonCode::WildcardSubscriptionNotSupported => {
                 write!(f, "Wildcard subscription not supported!")
             }
+            ReasonCode::InvalidArgument => write!(f, "Invalid argument!"),
             ReasonCode::TimerNotSupported => write!(f, "Timer implementation is not provided"),
             ReasonCode::BuffError => write!(f, "Error encountered during write / read from packet"),
             ReasonCode::NetworkError => write!(f, "Unknown error!"),
         }
     }
 }
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReasonCode {}
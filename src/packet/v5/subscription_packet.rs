@@ -34,6 +34,26 @@ use crate::utils::types::{BufferError, TopicFilter};
 use super::packet_type::PacketType;
 use super::property::Property;
 
+/// Retain handling subscription option tells the broker whether retained messages
+/// are sent when the subscription is established.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RetainHandling {
+    AlwaysSend,
+    SendIfSubscriptionDoesNotExist,
+    DoNotSend,
+}
+
+impl From<RetainHandling> for u8 {
+    fn from(value: RetainHandling) -> Self {
+        match value {
+            RetainHandling::AlwaysSend => 0,
+            RetainHandling::SendIfSubscriptionDoesNotExist => 1,
+            RetainHandling::DoNotSend => 2,
+        }
+    }
+}
+
 pub struct SubscriptionPacket<'a, const MAX_FILTERS: usize, const MAX_PROPERTIES: usize> {
     pub fixed_header: u8,
     pub remain_len: u32,
@@ -48,14 +68,45 @@ impl<'a, const MAX_FILTERS: usize, const MAX_PROPERTIES: usize>
     SubscriptionPacket<'a, MAX_FILTERS, MAX_PROPERTIES>
 {
     pub fn add_new_filter(&mut self, topic_name: &'a str, qos: QualityOfService) {
+        self.add_new_filter_with_retain_handling(topic_name, qos, RetainHandling::AlwaysSend);
+    }
+
+    pub fn add_new_filter_with_retain_handling(
+        &mut self,
+        topic_name: &'a str,
+        qos: QualityOfService,
+        retain_handling: RetainHandling,
+    ) {
         let len = topic_name.len();
         let mut new_filter = TopicFilter::new();
         new_filter.filter.string = topic_name;
         new_filter.filter.len = len as u16;
         new_filter.sub_options |= <QualityOfService as Into<u8>>::into(qos) >> 1;
+        new_filter.sub_options |= <RetainHandling as Into<u8>>::into(retain_handling) << 4;
         self.topic_filters.push(new_filter);
         self.topic_filter_len += 1;
     }
+
+    /// Returns the remaining length of the packet, which is the length of the packet
+    /// without the fixed header and the remaining length itself.
+    pub fn remaining_len(&self) -> Result<u32, BufferError> {
+        let property_len_enc: [u8; 4] = VariableByteIntegerEncoder::encode(self.property_len)?;
+        let property_len_len = VariableByteIntegerEncoder::len(property_len_enc);
+        let filters_len: u32 = self
+            .topic_filters
+            .iter()
+            .map(|filter| filter.encoded_len() as u32)
+            .sum();
+        Ok(2 + property_len_len as u32 + self.property_len + filters_len)
+    }
+
+    /// Returns the number of bytes the whole packet takes when encoded. It can be used
+    /// to check that the packet fits the send buffer and the Maximum Packet Size of the broker.
+    pub fn encoded_len(&self) -> Result<usize, BufferError> {
+        let rm_ln = self.remaining_len()?;
+        let rm_ln_len = VariableByteIntegerEncoder::len(VariableByteIntegerEncoder::encode(rm_ln)?);
+        Ok(1 + rm_ln_len + rm_ln as usize)
+    }
 }
 
 impl<'a, const MAX_FILTERS: usize, const MAX_PROPERTIES: usize> Packet<'a>
@@ -76,20 +127,7 @@ impl<'a, const MAX_FILTERS: usize, const MAX_PROPERTIES: usize> Packet<'a>
     fn encode(&mut self, buffer: &mut [u8], buffer_len: usize) -> Result<usize, BufferError> {
         let mut buff_writer = BuffWriter::new(buffer, buffer_len);
 
-        let mut rm_ln = self.property_len;
-        let property_len_enc: [u8; 4] = VariableByteIntegerEncoder::encode(self.property_len)?;
-        let property_len_len = VariableByteIntegerEncoder::len(property_len_enc);
-
-        let mut lt = 0;
-        let mut filters_len = 0;
-        loop {
-            filters_len = filters_len + self.topic_filters.get(lt).unwrap().filter.len + 3;
-            lt += 1;
-            if lt == self.topic_filter_len as usize {
-                break;
-            }
-        }
-        rm_ln = rm_ln + property_len_len as u32 + 2 + filters_len as u32;
+        let rm_ln = self.remaining_len()?;
 
         buff_writer.write_u8(self.fixed_header)?;
         buff_writer.write_variable_byte_int(rm_ln)?;
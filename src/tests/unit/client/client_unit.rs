@@ -0,0 +1,869 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use rand_core::{impls, RngCore};
+use tokio_test::block_on;
+
+use crate::client::client::MqttClient;
+use crate::client::client_config::{ClientConfig, MqttVersion};
+use crate::packet::v5::property::Property;
+use crate::packet::v5::publish_packet::QualityOfService;
+use crate::packet::v5::reason_codes::ReasonCode;
+use crate::tests::unit::mock::MockNetwork;
+use crate::utils::rng_generator::CountingRng;
+
+/// Rng returning the prepared values.
+struct SequenceRng(&'static [u32]);
+
+impl RngCore for SequenceRng {
+    fn next_u32(&mut self) -> u32 {
+        let (value, rest) = self.0.split_first().unwrap();
+        self.0 = rest;
+        *value
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Creates the `MqttClient` named `$client` over the `$network` with 100 Bytes long buffers.
+macro_rules! mock_client {
+    ($client:ident, $network:expr, $config:expr) => {
+        let mut write_buffer = [0; 100];
+        let mut recv_buffer = [0; 100];
+        let mut $client = MqttClient::new(
+            $network,
+            &mut write_buffer,
+            100,
+            &mut recv_buffer,
+            100,
+            $config,
+        );
+    };
+}
+
+fn client_config(version: MqttVersion) -> ClientConfig<'static, 5, CountingRng> {
+    ClientConfig::new(version, CountingRng(0))
+}
+
+#[test]
+fn test_subscribe_wildcard_not_supported() {
+    // CONNACK with Wildcard Subscription Available set to 0
+    let network = MockNetwork::new(&[0x20, 0x05, 0x00, 0x00, 0x02, 0x28, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert!(client.is_connected());
+
+    let res = block_on(client.subscribe_to_topic("sensors/#"));
+    assert_eq!(res, Err(ReasonCode::WildcardSubscriptionNotSupported));
+}
+
+#[test]
+fn test_subscribe_shared_not_supported() {
+    // CONNACK with Shared Subscription Available set to 0
+    let network = MockNetwork::new(&[0x20, 0x05, 0x00, 0x00, 0x02, 0x2A, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert!(client.is_connected());
+
+    let res = block_on(client.subscribe_to_topic("$share/group/topic"));
+    assert_eq!(res, Err(ReasonCode::SharedSubscriptionNotSupported));
+}
+
+#[test]
+fn test_subscription_identifier_range() {
+    let mut config = client_config(MqttVersion::MQTTv5);
+    assert_eq!(
+        config.add_subscription_identifier(0),
+        Err(ReasonCode::InvalidArgument)
+    );
+    assert_eq!(
+        config.add_subscription_identifier(268_435_456),
+        Err(ReasonCode::InvalidArgument)
+    );
+    assert_eq!(config.subscription_identifier, None);
+    assert!(config.add_subscription_identifier(268_435_455).is_ok());
+    assert_eq!(config.subscription_identifier, Some(268_435_455));
+}
+
+#[test]
+fn test_subscribe_subscription_identifier_invalid() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.subscription_identifier = Some(0);
+    mock_client!(client, network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.subscribe_to_topic("test/topic"));
+    assert_eq!(res, Err(ReasonCode::InvalidArgument));
+}
+
+#[test]
+fn test_subscribe_subscription_identifier_not_supported() {
+    // CONNACK with Subscription Identifier Available set to 0
+    let network = MockNetwork::new(&[0x20, 0x05, 0x00, 0x00, 0x02, 0x29, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    assert!(config.add_subscription_identifier(7).is_ok());
+    mock_client!(client, network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.subscribe_to_topic("test/topic"));
+    assert_eq!(res, Err(ReasonCode::SubscriptionIdentifiersNotSupported));
+}
+
+// With the tls feature every read has to return exactly one packet
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_subscribe_subscription_identifier() {
+    // CONNACK and SUBACK for the packet identifier 5
+    let mut network = MockNetwork::new(&[
+        0x20, 0x03, 0x00, 0x00, 0x00, 0x90, 0x04, 0x00, 0x05, 0x00, 0x00,
+    ]);
+    let mut config = ClientConfig::<5, _>::new(MqttVersion::MQTTv5, SequenceRng(&[5]));
+    assert!(config.add_subscription_identifier(7).is_ok());
+    mock_client!(client, &mut network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert!(block_on(client.subscribe_to_topic("a/b")).is_ok());
+    // SUBSCRIBE with the Subscription Identifier property set to 7
+    let output = &network.output;
+    assert_eq!(
+        &output[output.len() - 13..],
+        &[0x82, 0x0B, 0x00, 0x05, 0x02, 0x0B, 0x07, 0x00, 0x03, b'a', b'/', b'b', 0x00]
+    );
+}
+
+// With the tls feature every read has to return exactly one packet
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_receive_zero_subscription_identifier() {
+    // CONNACK and PUBLISH with the Subscription Identifier 0
+    let network = MockNetwork::new(&[
+        0x20, 0x03, 0x00, 0x00, 0x00, 0x30, 0x0B, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x02, 0x0B,
+        0x00, 0x68, 0x69,
+    ]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.receive_message());
+    assert_eq!(res.err(), Some(ReasonCode::ProtocolError));
+}
+
+// With the tls feature every read has to return exactly one packet
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_last_publish_flags() {
+    for (version, connack, publish) in [
+        (
+            MqttVersion::MQTTv5,
+            &[0x20, 0x03, 0x00, 0x00, 0x00][..],
+            &[
+                0x3B, 0x0B, 0x00, 0x04, b't', b'e', b's', b't', 0x00, 0x05, 0x00, b'h', b'i',
+            ][..],
+        ),
+        (
+            MqttVersion::MQTTv3,
+            &[0x20, 0x02, 0x00, 0x00][..],
+            &[
+                0x3B, 0x0A, 0x00, 0x04, b't', b'e', b's', b't', 0x00, 0x05, b'h', b'i',
+            ][..],
+        ),
+    ] {
+        // PUBLISH with DUP, QoS 1 and RETAIN flags set
+        let mut input: heapless::Vec<u8, 32> = heapless::Vec::new();
+        input.extend_from_slice(connack).unwrap();
+        input.extend_from_slice(publish).unwrap();
+        let network = MockNetwork::new(&input);
+        mock_client!(client, network, client_config(version));
+
+        assert!(block_on(client.connect_to_broker()).is_ok());
+        assert_eq!(client.last_publish_flags(), None);
+        assert!(block_on(client.receive_message()).is_ok());
+        assert_eq!(client.last_publish_flags(), Some(0x0B));
+    }
+}
+
+// With the tls feature every read has to return exactly one packet
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_subscribe_topics_one_subscription_identifier() {
+    // CONNACK and SUBACK for the packet identifier 5 with two granted filters
+    let mut network = MockNetwork::new(&[
+        0x20, 0x03, 0x00, 0x00, 0x00, 0x90, 0x05, 0x00, 0x05, 0x00, 0x00, 0x00,
+    ]);
+    let mut config = ClientConfig::<5, _>::new(MqttVersion::MQTTv5, SequenceRng(&[5]));
+    assert!(config.add_subscription_identifier(7).is_ok());
+    mock_client!(client, &mut network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let mut topics: heapless::Vec<&str, 2> = heapless::Vec::new();
+    topics.push("a").unwrap();
+    topics.push("b").unwrap();
+    assert!(block_on(client.subscribe_to_topics(&topics)).is_ok());
+    // Subscription Identifier is sent once in the properties, not for every filter
+    let output = &network.output;
+    assert_eq!(
+        &output[output.len() - 15..],
+        &[
+            0x82, 0x0D, 0x00, 0x05, 0x02, 0x0B, 0x07, 0x00, 0x01, b'a', 0x00, 0x00, 0x01, b'b',
+            0x00
+        ]
+    );
+}
+
+// With the tls feature every read has to return exactly one packet
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_clear_retained() {
+    // CONNACK and PUBACK for the packet identifier 1
+    let mut network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00, 0x40, 0x02, 0x00, 0x01]);
+    mock_client!(client, &mut network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.clear_retained("t", QualityOfService::QoS1));
+    assert_eq!(res, Ok(Some(1)));
+    // Retained PUBLISH with QoS 1 and the empty message
+    assert_eq!(
+        network.output[network.output.len() - 8..],
+        [0x33, 0x06, 0x00, 0x01, b't', 0x00, 0x01, 0x00]
+    );
+}
+
+#[test]
+fn test_clear_retained_qos0() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.clear_retained("t", QualityOfService::QoS0));
+    assert_eq!(res, Ok(None));
+}
+
+#[test]
+fn test_server_keep_alive_disabled() {
+    // CONNACK with Server Keep Alive set to 0
+    let network = MockNetwork::new(&[0x20, 0x06, 0x00, 0x00, 0x03, 0x13, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert_eq!(client.keep_alive(), 60);
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.keep_alive(), 0);
+}
+
+#[test]
+fn test_keep_alive_from_config() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.keep_alive = 30;
+    mock_client!(client, network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.keep_alive(), 30);
+}
+
+#[test]
+fn test_session_expiry_interval_from_connack() {
+    // CONNACK with Session Expiry Interval set to 120
+    let network = MockNetwork::new(&[0x20, 0x08, 0x00, 0x00, 0x05, 0x11, 0x00, 0x00, 0x00, 0x78]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.add_property(Property::SessionExpiryInterval(3600));
+    mock_client!(client, network, config);
+
+    assert_eq!(client.session_expiry_interval(), 3600);
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.session_expiry_interval(), 120);
+}
+
+#[test]
+fn test_session_expiry_interval_from_config() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.add_property(Property::SessionExpiryInterval(3600));
+    mock_client!(client, network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.session_expiry_interval(), 3600);
+}
+
+#[test]
+fn test_required_qos_not_supported() {
+    // CONNACK with Maximum QoS set to 0
+    let mut network = MockNetwork::new(&[0x20, 0x05, 0x00, 0x00, 0x02, 0x24, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.add_required_qos(QualityOfService::QoS1);
+    mock_client!(client, &mut network, config);
+
+    let res = block_on(client.connect_to_broker());
+    assert_eq!(res, Err(ReasonCode::QoSNotSupported));
+    assert!(!client.is_connected());
+    assert_eq!(client.maximum_qos(), QualityOfService::QoS0);
+    assert_eq!(
+        client.last_close_reason(),
+        Some(ReasonCode::QoSNotSupported)
+    );
+    // DISCONNECT with QoS not supported reason
+    assert_eq!(
+        network.output[network.output.len() - 4..],
+        [0xE0, 0x02, 0x9B, 0x00]
+    );
+}
+
+#[test]
+fn test_required_qos_not_supported_v3() {
+    let mut network = MockNetwork::new(&[0x20, 0x02, 0x00, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv3);
+    config.add_required_qos(QualityOfService::QoS2);
+    mock_client!(client, &mut network, config);
+
+    let res = block_on(client.connect_to_broker());
+    assert_eq!(res, Err(ReasonCode::QoSNotSupported));
+    assert!(!client.is_connected());
+    assert_eq!(network.output[network.output.len() - 2..], [0xE0, 0x00]);
+}
+
+#[test]
+fn test_required_qos_supported() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.add_required_qos(QualityOfService::QoS1);
+    mock_client!(client, network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.maximum_qos(), QualityOfService::QoS1);
+}
+
+#[test]
+fn test_reconnect() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert!(block_on(client.disconnect()).is_ok());
+    assert!(!client.is_connected());
+    assert_eq!(
+        block_on(client.connect_to_broker()),
+        Err(ReasonCode::NetworkError)
+    );
+
+    client.reset_for_reconnect(MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]));
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert!(client.is_connected());
+}
+
+#[test]
+fn test_disconnect_with_reason() {
+    let mut network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, &mut network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.disconnect_with_reason(ReasonCode::AdministrativeAction));
+    assert!(res.is_ok());
+    assert!(!client.is_connected());
+    assert_eq!(
+        network.output[network.output.len() - 4..],
+        [0xE0, 0x02, 0x98, 0x00]
+    );
+}
+
+#[test]
+fn test_disconnect_with_will() {
+    let mut network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.add_will("will/topic", b"gone", false);
+    mock_client!(client, &mut network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert!(block_on(client.disconnect_with_will()).is_ok());
+    assert!(!client.is_connected());
+    assert_eq!(
+        network.output[network.output.len() - 4..],
+        [0xE0, 0x02, 0x04, 0x00]
+    );
+}
+
+#[test]
+fn test_disconnect_with_will_v3() {
+    let network = MockNetwork::new(&[0x20, 0x02, 0x00, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv3);
+    config.add_will("will/topic", b"gone", false);
+    mock_client!(client, network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.disconnect_with_will());
+    assert_eq!(res, Err(ReasonCode::UnsupportedProtocolVersion));
+    assert!(client.is_connected());
+    assert_eq!(client.last_close_reason(), None);
+}
+
+#[test]
+fn test_disconnect_suppresses_will() {
+    let mut network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.add_will("will/topic", b"gone", false);
+    mock_client!(client, &mut network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert!(block_on(client.disconnect()).is_ok());
+    assert_eq!(
+        network.output[network.output.len() - 4..],
+        [0xE0, 0x02, 0x00, 0x00]
+    );
+}
+
+#[test]
+fn test_disconnect_with_invalid_reason() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.disconnect_with_reason(ReasonCode::GrantedQoS1));
+    assert_eq!(res, Err(ReasonCode::InvalidArgument));
+    let res = block_on(client.disconnect_with_reason(ReasonCode::ServerMoved));
+    assert_eq!(res, Err(ReasonCode::InvalidArgument));
+    assert!(client.is_connected());
+}
+
+#[test]
+fn test_disconnect_with_reason_v3() {
+    let mut network = MockNetwork::new(&[0x20, 0x02, 0x00, 0x00]);
+    mock_client!(client, &mut network, client_config(MqttVersion::MQTTv3));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.disconnect_with_reason(ReasonCode::AdministrativeAction));
+    assert_eq!(res, Err(ReasonCode::UnsupportedProtocolVersion));
+    assert!(client.is_connected());
+    let res = block_on(client.disconnect_with_reason(ReasonCode::Success));
+    assert!(res.is_ok());
+    assert!(!client.is_connected());
+    assert_eq!(network.output[network.output.len() - 2..], [0xE0, 0x00]);
+}
+
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_send_message_with_identifier() {
+    // CONNACK and PUBACK for the packet identifier 0x1234
+    let mut network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00, 0x40, 0x02, 0x12, 0x34]);
+    mock_client!(client, &mut network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.send_message_with_identifier(
+        "t",
+        b"m",
+        QualityOfService::QoS1,
+        false,
+        0x1234,
+    ));
+    assert!(res.is_ok());
+    assert_eq!(
+        network.output[network.output.len() - 9..],
+        [0x32, 0x07, 0x00, 0x01, b't', 0x12, 0x34, 0x00, b'm']
+    );
+}
+
+#[test]
+fn test_send_message_with_zero_identifier() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res =
+        block_on(client.send_message_with_identifier("t", b"m", QualityOfService::QoS1, false, 0));
+    assert_eq!(res, Err(ReasonCode::ProtocolError));
+}
+
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_puback_no_matching_subscribers() {
+    // CONNACK and PUBACK for the packet identifier 1 with No matching subscribers reason
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00, 0x40, 0x03, 0x00, 0x01, 0x10]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.send_message_ack_reason("t", b"m", QualityOfService::QoS1, false));
+    assert_eq!(res, Ok(ReasonCode::NoMatchingSubscribers));
+    assert!(client.is_connected());
+}
+
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_puback_success() {
+    // CONNACK and PUBACK for the packet identifier 1 without the reason code
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00, 0x40, 0x02, 0x00, 0x01]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.send_message_ack_reason("t", b"m", QualityOfService::QoS1, false));
+    assert_eq!(res, Ok(ReasonCode::Success));
+}
+
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_puback_error() {
+    // CONNACK and PUBACK for the packet identifier 1 with Not authorized reason
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00, 0x40, 0x03, 0x00, 0x01, 0x87]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res =
+        block_on(client.send_message_with_identifier("t", b"m", QualityOfService::QoS1, false, 1));
+    assert_eq!(res, Err(ReasonCode::NotAuthorized));
+}
+
+#[test]
+fn test_send_message_qos0_success() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.send_message_ack_reason("t", b"m", QualityOfService::QoS0, false));
+    assert_eq!(res, Ok(ReasonCode::Success));
+}
+
+#[test]
+fn test_reconnect_with_assigned_identifier() {
+    // CONNACK with Assigned Client Identifier "auto1"
+    let mut network = MockNetwork::new(&[
+        0x20, 0x0B, 0x00, 0x00, 0x08, 0x12, 0x00, 0x05, b'a', b'u', b't', b'o', b'1',
+    ]);
+    let mut reconnect_network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, &mut network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.client_identifier(), "auto1");
+    assert!(block_on(client.disconnect()).is_ok());
+
+    client.reset_for_reconnect(&mut reconnect_network);
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.client_identifier(), "auto1");
+    let output = &reconnect_network.output;
+    assert_eq!(
+        output[output.len() - 7..],
+        [0x00, 0x05, b'a', b'u', b't', b'o', b'1']
+    );
+}
+
+#[test]
+fn test_response_information() {
+    // CONNACK with Response Information "resp"
+    let mut network = MockNetwork::new(&[
+        0x20, 0x0A, 0x00, 0x00, 0x07, 0x1A, 0x00, 0x04, b'r', b'e', b's', b'p',
+    ]);
+    let mut reconnect_network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.add_request_response_information();
+    mock_client!(client, &mut network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.response_information(), Some("resp"));
+    assert_eq!(
+        client.response_topic_for::<16>("c1").as_deref(),
+        Some("resp/c1")
+    );
+    assert_eq!(client.response_topic_for::<6>("c1"), None);
+    assert!(block_on(client.disconnect()).is_ok());
+
+    client.reset_for_reconnect(&mut reconnect_network);
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.response_information(), None);
+    assert_eq!(client.response_topic_for::<16>("c1"), None);
+    assert!(reconnect_network
+        .output
+        .windows(2)
+        .any(|prop| prop == [0x19, 0x01]));
+}
+
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_receive_packet_too_large() {
+    // CONNACK and PUBLISH with 26 Bytes long packet
+    let mut network = MockNetwork::new(&[
+        0x20, 0x03, 0x00, 0x00, 0x00, 0x30, 0x18, 0x00, 0x04, b't', b'e', b's', b't', 0x00, b'0',
+        b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9', b'0', b'1', b'2', b'3', b'4', b'5',
+        b'6',
+    ]);
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.max_packet_size = 20;
+    mock_client!(client, &mut network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.receive_message()).map(|_| ());
+    assert_eq!(res, Err(ReasonCode::PacketTooLarge));
+    assert!(!client.is_connected());
+    assert_eq!(client.last_close_reason(), Some(ReasonCode::PacketTooLarge));
+    assert_eq!(
+        network.output[network.output.len() - 4..],
+        [0xE0, 0x02, 0x95, 0x00]
+    );
+}
+
+#[test]
+fn test_publish_size() {
+    for (version, connack) in [
+        (MqttVersion::MQTTv5, &[0x20, 0x03, 0x00, 0x00, 0x00][..]),
+        (MqttVersion::MQTTv3, &[0x20, 0x02, 0x00, 0x00][..]),
+    ] {
+        let mut network = MockNetwork::new(connack);
+        mock_client!(client, &mut network, client_config(version));
+
+        assert!(block_on(client.connect_to_broker()).is_ok());
+        let size = client
+            .publish_size("test/topic", 11, QualityOfService::QoS0)
+            .unwrap();
+        let res = block_on(client.send_message(
+            "test/topic",
+            b"Hello world",
+            QualityOfService::QoS0,
+            false,
+        ));
+        assert!(res.is_ok());
+        let output = &network.output;
+        assert_eq!(output[output.len() - size], 0x30);
+        assert_eq!(output[output.len() - size + 1] as usize, size - 2);
+    }
+}
+
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_publish_size_qos1() {
+    // CONNACK and PUBACK for the packet identifier 1
+    let mut network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00, 0x40, 0x02, 0x00, 0x01]);
+    mock_client!(client, &mut network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let size = client
+        .publish_size("test/topic", 11, QualityOfService::QoS1)
+        .unwrap();
+    let res = block_on(client.send_message_with_identifier(
+        "test/topic",
+        b"Hello world",
+        QualityOfService::QoS1,
+        false,
+        1,
+    ));
+    assert!(res.is_ok());
+    let output = &network.output;
+    assert_eq!(output[output.len() - size], 0x32);
+    assert_eq!(output[output.len() - size + 1] as usize, size - 2);
+}
+
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_last_close_reason_from_broker() {
+    // CONNACK and DISCONNECT with Session taken over reason
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00, 0xE0, 0x01, 0x8E]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert_eq!(client.last_close_reason(), None);
+    let res = block_on(client.receive_message()).map(|_| ());
+    assert_eq!(res, Err(ReasonCode::SessionTakeOver));
+    assert_eq!(
+        client.last_close_reason(),
+        Some(ReasonCode::SessionTakeOver)
+    );
+}
+
+#[test]
+fn test_last_close_reason_from_client() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.disconnect_with_reason(ReasonCode::MessageRateTooHigh));
+    assert!(res.is_ok());
+    assert_eq!(
+        client.last_close_reason(),
+        Some(ReasonCode::MessageRateTooHigh)
+    );
+}
+
+#[test]
+fn test_last_close_reason_network_error() {
+    // Connection is closed by the broker after CONNACK
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.receive_message()).map(|_| ());
+    assert_eq!(res, Err(ReasonCode::NetworkError));
+    assert_eq!(client.last_close_reason(), Some(ReasonCode::NetworkError));
+}
+
+#[test]
+fn test_max_packet_size_property_added_once() {
+    let mut config = client_config(MqttVersion::MQTTv5);
+    config.add_max_packet_size_as_prop();
+    config.max_packet_size = 1024;
+    config.add_max_packet_size_as_prop();
+    assert_eq!(config.properties.len(), 1);
+    assert!(matches!(
+        config.properties[0],
+        Property::MaximumPacketSize(1024)
+    ));
+}
+
+#[cfg(feature = "metrics")]
+#[test]
+fn test_stats() {
+    use crate::packet::v5::packet_type::PacketType;
+
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let stats = client.stats();
+    assert_eq!(stats.sent(PacketType::Connect), 1);
+    assert_eq!(stats.received(PacketType::Connack), 1);
+    assert_eq!(stats.received(PacketType::Publish), 0);
+    assert_eq!(stats.bytes_received, 5);
+    assert!(stats.bytes_sent > 0);
+    assert_eq!(stats.publish_in_flight, 0);
+}
+
+#[cfg(all(feature = "metrics", not(feature = "tls")))]
+#[test]
+fn test_stats_publish_in_flight() {
+    let network = MockNetwork::new(&[
+        0x20, 0x03, 0x00, 0x00, 0x00, // CONNACK
+        0x40, 0x03, 0x00, 0x01, 0x00, // PUBACK for packet 1
+    ]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert!(block_on(client.send_message("a", b"1", QualityOfService::QoS1, false)).is_ok());
+    assert_eq!(client.stats().publish_in_flight, 0);
+
+    assert!(block_on(client.send_message("a", b"2", QualityOfService::QoS1, false)).is_err());
+    assert_eq!(client.stats().publish_in_flight, 1);
+}
+
+#[cfg(feature = "wire-hook")]
+#[test]
+fn test_wire_hook() {
+    use crate::network::Direction;
+    use heapless::Vec;
+
+    let mut packets: Vec<(Direction, u8, usize), 4> = Vec::new();
+    {
+        let mut write_buffer = [0; 100];
+        let mut recv_buffer = [0; 100];
+        // The hook only has to outlive the client, not the buffers
+        let mut hook = |direction: Direction, packet: &[u8]| {
+            packets.push((direction, packet[0], packet.len())).unwrap();
+        };
+        let mut client = MqttClient::new(
+            MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]),
+            &mut write_buffer,
+            100,
+            &mut recv_buffer,
+            100,
+            client_config(MqttVersion::MQTTv5),
+        );
+        client.set_wire_hook(Some(&mut hook));
+
+        assert!(block_on(client.connect_to_broker()).is_ok());
+        // The hook is kept when the client connects over a new network
+        client.reset_for_reconnect(MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]));
+        assert!(block_on(client.connect_to_broker()).is_ok());
+    }
+    assert_eq!(packets.len(), 4);
+    assert_eq!(packets[0].0, Direction::Sent);
+    assert_eq!(packets[0].1, 0x10);
+    assert_eq!(packets[1], (Direction::Received, 0x20, 5));
+    assert_eq!(packets[3], (Direction::Received, 0x20, 5));
+}
+
+// With the tls feature every read has to return exactly one packet
+#[cfg(not(feature = "tls"))]
+#[test]
+fn test_packet_identifier_not_zero() {
+    // CONNACK and SUBACK for the packet identifier 5
+    let network = MockNetwork::new(&[
+        0x20, 0x03, 0x00, 0x00, 0x00, 0x90, 0x04, 0x00, 0x05, 0x00, 0x00,
+    ]);
+    // Truncated to u16 the first value is 0
+    let config = ClientConfig::<5, _>::new(MqttVersion::MQTTv5, SequenceRng(&[0x10000, 5]));
+    mock_client!(client, network, config);
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    assert!(block_on(client.subscribe_to_topic("test/topic")).is_ok());
+}
+
+#[test]
+fn test_connect_rejected() {
+    // CONNACK with Not authorized reason code
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x87, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    let res = block_on(client.connect_to_broker());
+    assert_eq!(res, Err(ReasonCode::NotAuthorized));
+    assert!(!client.is_connected());
+}
+
+#[test]
+fn test_connect_rejected_v3() {
+    // CONNACK with Bad user name or password return code
+    let network = MockNetwork::new(&[0x20, 0x02, 0x00, 0x04]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv3));
+
+    let res = block_on(client.connect_to_broker());
+    assert_eq!(res, Err(ReasonCode::BadUserNameOrPassword));
+}
+
+#[test]
+fn test_connection_closed() {
+    let network = MockNetwork::new(&[]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    let res = block_on(client.connect_to_broker());
+    assert_eq!(res, Err(ReasonCode::NetworkError));
+    assert!(!client.is_connected());
+}
+
+// With the tls feature the whole packet is read at once, zero length read means
+// the connection was closed like without the feature
+#[cfg(feature = "tls")]
+#[test]
+fn test_tls_zero_length_read() {
+    let network = MockNetwork::new(&[0x20, 0x03, 0x00, 0x00, 0x00]);
+    mock_client!(client, network, client_config(MqttVersion::MQTTv5));
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    let res = block_on(client.receive_message()).map(|_| ());
+    assert_eq!(res, Err(ReasonCode::NetworkError));
+    assert!(!client.is_connected());
+    assert_eq!(client.last_close_reason(), Some(ReasonCode::NetworkError));
+}
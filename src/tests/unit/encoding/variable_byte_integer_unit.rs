@@ -47,7 +47,7 @@ fn test_decode_small() {
 
 #[test]
 fn test_encode() {
-    let encoded = VariableByteIntegerEncoder::encode(211_366_5);
+    let encoded = VariableByteIntegerEncoder::encode(2_113_665);
     assert!(encoded.is_ok());
     let res = encoded.unwrap();
     assert_eq!(res, [0x81, 0x81, 0x81, 0x01]);
@@ -78,3 +78,46 @@ fn test_encode_max() {
     assert!(encoded.is_err());
     assert_eq!(encoded.unwrap_err(), BufferError::EncodingError);
 }
+
+#[test]
+fn test_encode_decode_boundaries() {
+    let values: [(u32, usize); 8] = [
+        (127, 1),
+        (128, 2),
+        (16_383, 2),
+        (16_384, 3),
+        (2_097_151, 3),
+        (2_097_152, 4),
+        (268_435_455, 4),
+        (0, 1),
+    ];
+    for (value, len) in values {
+        let encoded = VariableByteIntegerEncoder::encode(value);
+        assert!(encoded.is_ok());
+        let res = encoded.unwrap();
+        assert_eq!(VariableByteIntegerEncoder::len(res), len);
+        assert_eq!(VariableByteIntegerDecoder::decode(res), Ok(value));
+    }
+}
+
+#[test]
+fn test_encode_too_large() {
+    let encoded = VariableByteIntegerEncoder::encode(VariableByteIntegerEncoder::MAX_ENCODABLE + 1);
+    assert_eq!(encoded.unwrap_err(), BufferError::EncodingError);
+}
+
+#[test]
+fn test_decode_max() {
+    static BUFFER: VariableByteInteger = [0xFF, 0xFF, 0xFF, 0x7F];
+
+    let decoded = VariableByteIntegerDecoder::decode(BUFFER);
+    assert_eq!(decoded, Ok(VariableByteIntegerEncoder::MAX_ENCODABLE));
+}
+
+#[test]
+fn test_decode_too_long() {
+    static BUFFER: VariableByteInteger = [0xFF, 0xFF, 0xFF, 0xFF];
+
+    let decoded = VariableByteIntegerDecoder::decode(BUFFER);
+    assert_eq!(decoded, Err(BufferError::DecodingError));
+}
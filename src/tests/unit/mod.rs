@@ -22,6 +22,9 @@
  * SOFTWARE.
  */
 
+pub mod client;
 pub mod encoding;
+pub mod mock;
+pub mod network;
 pub mod packet;
 pub mod utils;
@@ -0,0 +1,154 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use embedded_io_async::{Read, Write};
+use futures::future::join;
+use tokio_test::block_on;
+
+use crate::client::client::MqttClient;
+use crate::client::client_config::{ClientConfig, MqttVersion};
+use crate::network::duplex::{Duplex, DuplexEnd};
+use crate::packet::v5::publish_packet::QualityOfService;
+use crate::packet::v5::reason_codes::ReasonCode;
+use crate::utils::rng_generator::CountingRng;
+
+/// Reads one whole MQTT packet with the remaining length below 128 from the `broker` end.
+async fn read_packet(broker: &mut DuplexEnd<'_, 256>, packet: &mut [u8]) -> usize {
+    broker.read_exact(&mut packet[..2]).await.unwrap();
+    let len = 2 + packet[1] as usize;
+    broker.read_exact(&mut packet[2..len]).await.unwrap();
+    len
+}
+
+#[test]
+fn test_read_write() {
+    let mut duplex = Duplex::<8>::new();
+    let (mut first, mut second) = duplex.split();
+    assert_eq!(block_on(first.write(&[1, 2, 3])), Ok(3));
+    let mut buf = [0u8; 8];
+    assert_eq!(block_on(second.read(&mut buf)), Ok(3));
+    assert_eq!(buf[..3], [1, 2, 3]);
+    drop(first);
+    assert_eq!(block_on(second.read(&mut buf)), Ok(0));
+    assert!(block_on(second.write(&[1])).is_err());
+}
+
+#[test]
+fn test_connect_rejected() {
+    let mut duplex = Duplex::<256>::new();
+    let (client_end, mut broker) = duplex.split();
+    // CONNACK with Not authorized reason code is prepared before connecting
+    assert!(block_on(broker.write_all(&[0x20, 0x03, 0x00, 0x87, 0x00])).is_ok());
+
+    let config = ClientConfig::<5, _>::new(MqttVersion::MQTTv5, CountingRng(0));
+    let mut write_buffer = [0; 100];
+    let mut recv_buffer = [0; 100];
+    let mut client = MqttClient::new(
+        client_end,
+        &mut write_buffer,
+        100,
+        &mut recv_buffer,
+        100,
+        config,
+    );
+    let res = block_on(client.connect_to_broker());
+    assert_eq!(res, Err(ReasonCode::NotAuthorized));
+
+    let mut packet = [0u8; 100];
+    let len = block_on(read_packet(&mut broker, &mut packet));
+    assert_eq!(packet[0], 0x10);
+    assert_eq!(packet[2..8], [0x00, 0x04, b'M', b'Q', b'T', b'T']);
+    assert_eq!(len, 2 + packet[1] as usize);
+}
+
+#[test]
+fn test_publish_with_scripted_broker() {
+    let mut duplex = Duplex::<256>::new();
+    let (client_end, mut broker) = duplex.split();
+    let config = ClientConfig::<5, _>::new(MqttVersion::MQTTv5, CountingRng(0));
+    let mut write_buffer = [0; 100];
+    let mut recv_buffer = [0; 100];
+    let mut client = MqttClient::new(
+        client_end,
+        &mut write_buffer,
+        100,
+        &mut recv_buffer,
+        100,
+        config,
+    );
+
+    let client_flow = async {
+        client.connect_to_broker().await?;
+        client
+            .send_message_ack_reason("test/topic", b"hello", QualityOfService::QoS1, false)
+            .await
+    };
+    // Broker answers the CONNECT and the PUBLISH only after it receives them
+    let broker_flow = async {
+        let mut packet = [0u8; 100];
+        read_packet(&mut broker, &mut packet).await;
+        assert_eq!(packet[0], 0x10);
+        broker
+            .write_all(&[0x20, 0x03, 0x00, 0x00, 0x00])
+            .await
+            .unwrap();
+
+        let len = read_packet(&mut broker, &mut packet).await;
+        assert_eq!(packet[0], 0x32);
+        assert_eq!(packet[len - 5..len], *b"hello");
+        // Packet identifier follows the topic and its length
+        let id = [packet[14], packet[15]];
+        broker
+            .write_all(&[0x40, 0x03, id[0], id[1], 0x10])
+            .await
+            .unwrap();
+    };
+
+    let (res, _) = block_on(join(client_flow, broker_flow));
+    assert_eq!(res, Ok(ReasonCode::NoMatchingSubscribers));
+}
+
+#[test]
+fn test_broker_closed_connection() {
+    let mut duplex = Duplex::<256>::new();
+    let (client_end, mut broker) = duplex.split();
+    assert!(block_on(broker.write_all(&[0x20, 0x03, 0x00, 0x00, 0x00])).is_ok());
+    let config = ClientConfig::<5, _>::new(MqttVersion::MQTTv5, CountingRng(0));
+    let mut write_buffer = [0; 100];
+    let mut recv_buffer = [0; 100];
+    let mut client = MqttClient::new(
+        client_end,
+        &mut write_buffer,
+        100,
+        &mut recv_buffer,
+        100,
+        config,
+    );
+
+    assert!(block_on(client.connect_to_broker()).is_ok());
+    drop(broker);
+    let res = block_on(client.receive_message()).map(|_| ());
+    assert_eq!(res, Err(ReasonCode::NetworkError));
+    assert!(!client.is_connected());
+}
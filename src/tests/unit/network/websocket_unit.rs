@@ -0,0 +1,157 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use embedded_io_async::{Read, Write};
+use tokio_test::block_on;
+
+use crate::network::websocket::{WebSocketConnection, WebSocketError};
+use crate::tests::unit::mock::MockNetwork;
+use crate::utils::rng_generator::CountingRng;
+
+fn handshake(response: &str) -> Result<(), WebSocketError<core::convert::Infallible>> {
+    let stream = MockNetwork::new(response.as_bytes());
+    let mut ws = WebSocketConnection::new(stream, CountingRng(0));
+    block_on(ws.connect("localhost", "/mqtt"))
+}
+
+#[test]
+fn test_handshake() {
+    // Sec-WebSocket-Accept of the key generated by CountingRng(0)
+    let res = handshake(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Accept: JtLsuHLH4pCrwZnFgmzUQEmKv+A=\r\nSec-WebSocket-Protocol: mqtt\r\n\r\n",
+    );
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_handshake_header_case() {
+    let res = handshake(
+        "HTTP/1.1 101 Switching Protocols\r\nsec-websocket-protocol:mqtt\r\n\
+         SEC-WEBSOCKET-ACCEPT:  JtLsuHLH4pCrwZnFgmzUQEmKv+A= \r\n\r\n",
+    );
+    assert!(res.is_ok());
+}
+
+#[test]
+fn test_handshake_invalid_accept() {
+    let res = handshake(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Sec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\nSec-WebSocket-Protocol: mqtt\r\n\r\n",
+    );
+    assert!(matches!(res, Err(WebSocketError::Handshake)));
+}
+
+#[test]
+fn test_handshake_missing_protocol() {
+    let res = handshake(
+        "HTTP/1.1 101 Switching Protocols\r\nSec-WebSocket-Accept: JtLsuHLH4pCrwZnFgmzUQEmKv+A=\r\n\r\n",
+    );
+    assert!(matches!(res, Err(WebSocketError::Handshake)));
+}
+
+#[test]
+fn test_handshake_refused() {
+    let stream = MockNetwork::new(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+    let mut ws = WebSocketConnection::new(stream, CountingRng(0));
+    let res = block_on(ws.connect("localhost", "/mqtt"));
+    assert!(matches!(res, Err(WebSocketError::Handshake)));
+}
+
+#[test]
+fn test_write_frame() {
+    let stream = MockNetwork::new(&[]);
+    let mut ws = WebSocketConnection::new(stream, CountingRng(0));
+    let res = block_on(ws.write(&[0xC0, 0x00]));
+    assert_eq!(res.unwrap(), 2);
+    let stream = ws.into_inner();
+    assert_eq!(
+        stream.output.as_slice(),
+        &[0x82, 0x82, 0x01, 0x00, 0x00, 0x00, 0xC1, 0x00]
+    );
+}
+
+#[test]
+fn test_read_fragmented() {
+    // PINGRESP split into two frames with the ping control frame in between
+    let stream = MockNetwork::new(&[0x02, 0x01, 0xD0, 0x89, 0x00, 0x80, 0x01, 0x00]);
+    let mut ws = WebSocketConnection::new(stream, CountingRng(0));
+    let mut buf = [0u8; 2];
+    assert_eq!(block_on(ws.read(&mut buf)).unwrap(), 1);
+    assert_eq!(block_on(ws.read(&mut buf[1..])).unwrap(), 1);
+    assert_eq!(buf, [0xD0, 0x00]);
+    let stream = ws.into_inner();
+    assert_eq!(
+        stream.output.as_slice(),
+        &[0x8A, 0x80, 0x01, 0x00, 0x00, 0x00]
+    );
+}
+
+#[test]
+fn test_read_close() {
+    // Close frame with Normal closure status code
+    let stream = MockNetwork::new(&[0x88, 0x02, 0x03, 0xE8]);
+    let mut ws = WebSocketConnection::new(stream, CountingRng(0));
+    let mut buf = [0u8; 2];
+    assert_eq!(block_on(ws.read(&mut buf)).unwrap(), 0);
+    let stream = ws.into_inner();
+    assert_eq!(
+        stream.output.as_slice(),
+        &[0x88, 0x82, 0x01, 0x00, 0x00, 0x00, 0x02, 0xE8]
+    );
+}
+
+#[test]
+fn test_read_text_frame() {
+    // PINGRESP sent in a text frame is closed with Protocol error status code
+    let stream = MockNetwork::new(&[0x81, 0x02, 0xD0, 0x00]);
+    let mut ws = WebSocketConnection::new(stream, CountingRng(0));
+    let mut buf = [0u8; 2];
+    let res = block_on(ws.read(&mut buf));
+    assert!(matches!(res, Err(WebSocketError::Protocol)));
+    assert_eq!(block_on(ws.read(&mut buf)).unwrap(), 0);
+    let stream = ws.into_inner();
+    assert_eq!(
+        stream.output.as_slice(),
+        &[0x88, 0x82, 0x01, 0x00, 0x00, 0x00, 0x02, 0xEA]
+    );
+}
+
+#[test]
+fn test_read_masked_frame() {
+    let stream = MockNetwork::new(&[0x82, 0x81, 0x01, 0x02, 0x03, 0x04, 0xD1]);
+    let mut ws = WebSocketConnection::new(stream, CountingRng(0));
+    let mut buf = [0u8; 2];
+    let res = block_on(ws.read(&mut buf));
+    assert!(matches!(res, Err(WebSocketError::Protocol)));
+}
+
+#[test]
+fn test_read_reserved_bits() {
+    let stream = MockNetwork::new(&[0xC2, 0x01, 0xD0]);
+    let mut ws = WebSocketConnection::new(stream, CountingRng(0));
+    let mut buf = [0u8; 2];
+    let res = block_on(ws.read(&mut buf));
+    assert!(matches!(res, Err(WebSocketError::Protocol)));
+}
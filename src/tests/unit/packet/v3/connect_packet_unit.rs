@@ -0,0 +1,52 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::packet::v3::connect_packet::ConnectPacket;
+use crate::packet::v5::mqtt_packet::Packet;
+use crate::utils::types::EncodedString;
+
+#[test]
+fn test_encode() {
+    let mut buffer: [u8; 100] = [0; 100];
+    let mut connect = ConnectPacket::new();
+    connect.keep_alive = 60;
+    let mut client_id = EncodedString::new();
+    client_id.string = "test";
+    client_id.len = 4;
+    connect.add_client_id(&client_id);
+    let mut username = EncodedString::new();
+    username.string = "user";
+    username.len = 4;
+    connect.add_username(&username);
+    let res = connect.encode(&mut buffer, 100);
+
+    assert!(res.is_ok());
+    assert_eq!(
+        buffer[0..res.unwrap()],
+        [
+            0x10, 0x16, 0x00, 0x04, 0x4d, 0x51, 0x54, 0x54, 0x04, 0x82, 0x00, 0x3c, 0x00, 0x04,
+            0x74, 0x65, 0x73, 0x74, 0x00, 0x04, 0x75, 0x73, 0x65, 0x72
+        ]
+    )
+}
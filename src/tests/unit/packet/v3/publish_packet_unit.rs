@@ -0,0 +1,87 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::packet::v3::publish_packet::PublishPacket;
+use crate::packet::v5::mqtt_packet::Packet;
+use crate::packet::v5::packet_type::PacketType;
+use crate::packet::v5::publish_packet::QualityOfService;
+use crate::utils::buffer_reader::BuffReader;
+
+#[test]
+fn test_encode() {
+    let mut buffer: [u8; 12] = [0; 12];
+    let mut packet = PublishPacket::new();
+    packet.add_qos(QualityOfService::QoS1);
+    packet.add_identifier(23);
+    packet.add_topic_name("test");
+    packet.add_message("hi".as_bytes());
+    let res = packet.encode(&mut buffer, 12);
+    assert!(res.is_ok());
+    assert_eq!(res.unwrap(), 12);
+    assert_eq!(
+        buffer,
+        [0x32, 0x0A, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x00, 0x17, 0x68, 0x69]
+    )
+}
+
+#[test]
+fn test_decode() {
+    let buffer: [u8; 12] = [
+        0x32, 0x0A, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x00, 0x17, 0x68, 0x69,
+    ];
+    let mut packet = PublishPacket::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 12));
+    assert!(res.is_ok());
+    assert_eq!(packet.fixed_header, 0x32);
+    assert_eq!(packet.remain_len, 10);
+    assert_eq!(packet.topic_name.string, "test");
+    assert_eq!(packet.packet_identifier, 23);
+    assert_eq!(packet.message.unwrap(), "hi".as_bytes());
+}
+
+#[test]
+fn test_decode_qos0() {
+    let buffer: [u8; 10] = [0x30, 0x08, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x68, 0x69];
+    let mut packet = PublishPacket::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 10));
+    assert!(res.is_ok());
+    assert_eq!(packet.fixed_header, PacketType::Publish.into());
+    assert_eq!(packet.topic_name.string, "test");
+    assert_eq!(packet.message.unwrap(), "hi".as_bytes());
+}
+
+#[test]
+fn test_encoded_len() {
+    let mut buffer: [u8; 100] = [0; 100];
+    for qos in [QualityOfService::QoS0, QualityOfService::QoS1] {
+        let mut packet = PublishPacket::new();
+        packet.add_topic_name("test/topic");
+        packet.add_qos(qos);
+        packet.add_message(b"Hello world");
+        let res = packet.encode(&mut buffer, 100);
+        assert_eq!(res, packet.encoded_len());
+        assert_eq!(res, packet.encoded_len_with_message_len(11));
+        assert_eq!(packet.remaining_len(), Ok(res.unwrap() as u32 - 2));
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use heapless::Vec;
+
+use crate::packet::v5::auth_packet::AuthPacket;
+use crate::packet::v5::mqtt_packet::Packet;
+use crate::packet::v5::packet_type::PacketType;
+use crate::packet::v5::property::Property;
+use crate::packet::v5::reason_codes::ReasonCode;
+use crate::utils::buffer_reader::BuffReader;
+use crate::utils::types::{BinaryData, BufferError, EncodedString};
+
+#[test]
+fn test_encode() {
+    let mut buffer: [u8; 18] = [0; 18];
+    let mut packet = AuthPacket::<2>::new();
+    packet.add_reason_code(ReasonCode::ContinueAuth.into());
+    let mut method = EncodedString::new();
+    method.string = "SCRAM";
+    method.len = 5;
+    let mut data = BinaryData::new();
+    data.bin = &[0x01, 0x02, 0x03];
+    data.len = 3;
+    let mut props = Vec::<Property, 2>::new();
+    props.push(Property::AuthenticationMethod(method));
+    props.push(Property::AuthenticationData(data));
+    packet.property_len = packet.add_properties(&props);
+    let res = packet.encode(&mut buffer, 18);
+    assert!(res.is_ok());
+    assert_eq!(res.unwrap(), 18);
+    assert_eq!(
+        buffer,
+        [
+            0xF0, 0x10, 0x18, 0x0E, 0x15, 0x00, 0x05, 0x53, 0x43, 0x52, 0x41, 0x4D, 0x16, 0x00,
+            0x03, 0x01, 0x02, 0x03
+        ]
+    )
+}
+
+#[test]
+fn test_decode() {
+    let buffer: [u8; 18] = [
+        0xF0, 0x10, 0x18, 0x0E, 0x15, 0x00, 0x05, 0x53, 0x43, 0x52, 0x41, 0x4D, 0x16, 0x00, 0x03,
+        0x01, 0x02, 0x03,
+    ];
+    let mut packet = AuthPacket::<2>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 18));
+    assert!(res.is_ok());
+    assert_eq!(packet.fixed_header, PacketType::Auth.into());
+    assert_eq!(packet.remain_len, 16);
+    assert_eq!(packet.auth_reason, ReasonCode::ContinueAuth.into());
+    assert_eq!(packet.property_len, 14);
+    assert_eq!(packet.authentication_method(), Some("SCRAM"));
+    assert_eq!(packet.authentication_data(), Some(&[0x01, 0x02, 0x03][..]));
+}
+
+#[test]
+fn test_decode_reason_string_and_user_properties() {
+    let buffer: [u8; 16] = [
+        0xF0, 0x0E, 0x19, 0x0C, 0x1F, 0x00, 0x02, 0x68, 0x69, 0x26, 0x00, 0x01, 0x61, 0x00, 0x01,
+        0x62,
+    ];
+    let mut packet = AuthPacket::<2>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 16));
+    assert!(res.is_ok());
+    assert_eq!(packet.auth_reason, ReasonCode::ReAuthenticate.into());
+    assert_eq!(packet.reason_string(), Some("hi"));
+    let mut props = packet.user_properties();
+    assert_eq!(props.next(), Some(("a", "b")));
+    assert_eq!(props.next(), None);
+    assert_eq!(packet.authentication_method(), None);
+}
+
+#[test]
+fn test_decode_success_without_reason() {
+    let buffer: [u8; 2] = [0xF0, 0x00];
+    let mut packet = AuthPacket::<2>::new();
+    packet.auth_reason = 0x18;
+    let res = packet.decode(&mut BuffReader::new(&buffer, 2));
+    assert!(res.is_ok());
+    assert_eq!(packet.auth_reason, ReasonCode::Success.into());
+    assert_eq!(packet.property_len, 0);
+}
+
+#[test]
+fn test_decode_wrong_packet_type() {
+    let buffer: [u8; 2] = [0xE0, 0x00];
+    let mut packet = AuthPacket::<2>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 2));
+    assert_eq!(res, Err(BufferError::PacketTypeMismatch));
+}
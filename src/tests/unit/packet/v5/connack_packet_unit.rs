@@ -57,7 +57,7 @@ fn test_encode() {
 
 #[test]
 fn test_decode() {
-    let mut buffer: [u8; 8] = [
+    let buffer: [u8; 8] = [
         0x20,
         0x06,
         0x45,
@@ -78,9 +78,77 @@ fn test_decode() {
         ReasonCode::ServerMoved.into()
     );
     assert_eq!(connack_res.property_len, 3);
-    let prop = connack_res.properties.get(0).unwrap();
+    let prop = connack_res.properties.first().unwrap();
     assert_eq!(<&Property as Into<u8>>::into(prop), 0x21);
     if let Property::ReceiveMaximum(u) = *prop {
         assert_eq!(u, 21);
     }
 }
+
+#[test]
+fn test_decode_assigned_client_identifier() {
+    let buffer: [u8; 13] = [
+        0x20, 0x0B, 0x00, 0x00, 0x08, 0x12, 0x00, 0x05, b'a', b'u', b't', b'o', b'1',
+    ];
+    let mut connack_res = ConnackPacket::<2>::new();
+    let res = connack_res.decode(&mut BuffReader::new(&buffer, 13));
+
+    assert!(res.is_ok());
+    assert_eq!(connack_res.assigned_client_identifier(), Some("auto1"));
+}
+
+#[test]
+fn test_decode_user_properties() {
+    let buffer: [u8; 30] = [
+        0x20, 0x1C, 0x00, 0x00, 0x19, 0x26, 0x00, 0x06, b't', b'e', b'n', b'a', b'n', b't', 0x00,
+        0x02, b'4', b'2', 0x26, 0x00, 0x04, b'r', b'a', b't', b'e', 0x00, 0x03, b'1', b'0', b'0',
+    ];
+    let mut connack_res = ConnackPacket::<4>::new();
+    let res = connack_res.decode(&mut BuffReader::new(&buffer, 30));
+
+    assert!(res.is_ok());
+    let mut props = connack_res.user_properties();
+    assert_eq!(props.next(), Some(("tenant", "42")));
+    assert_eq!(props.next(), Some(("rate", "100")));
+    assert_eq!(props.next(), None);
+}
+
+#[test]
+fn test_decode_authentication() {
+    let buffer: [u8; 21] = [
+        0x20, 0x13, 0x00, 0x00, 0x10, 0x15, 0x00, 0x05, b'S', b'C', b'R', b'A', b'M', 0x16, 0x00,
+        0x05, 0x01, 0x02, 0x03, 0x04, 0x05,
+    ];
+    let mut connack_res = ConnackPacket::<2>::new();
+    let res = connack_res.decode(&mut BuffReader::new(&buffer, 21));
+
+    assert!(res.is_ok());
+    assert_eq!(connack_res.authentication_method(), Some("SCRAM"));
+    assert_eq!(
+        connack_res.authentication_data(),
+        Some(&[0x01, 0x02, 0x03, 0x04, 0x05][..])
+    );
+}
+
+#[test]
+fn test_decode_without_authentication() {
+    let buffer: [u8; 5] = [0x20, 0x03, 0x00, 0x00, 0x00];
+    let mut connack_res = ConnackPacket::<2>::new();
+    let res = connack_res.decode(&mut BuffReader::new(&buffer, 5));
+
+    assert!(res.is_ok());
+    assert_eq!(connack_res.authentication_method(), None);
+    assert_eq!(connack_res.authentication_data(), None);
+}
+
+#[test]
+fn test_decode_response_information() {
+    let buffer: [u8; 12] = [
+        0x20, 0x0A, 0x00, 0x00, 0x07, 0x1A, 0x00, 0x04, b'r', b'e', b's', b'p',
+    ];
+    let mut connack_res = ConnackPacket::<2>::new();
+    let res = connack_res.decode(&mut BuffReader::new(&buffer, 12));
+
+    assert!(res.is_ok());
+    assert_eq!(connack_res.response_information(), Some("resp"));
+}
@@ -22,8 +22,10 @@
  * SOFTWARE.
  */
 
+use crate::client::client_config::{ClientConfig, MqttVersion};
 use crate::packet::v5::connect_packet::ConnectPacket;
 use crate::packet::v5::mqtt_packet::Packet;
+use crate::utils::rng_generator::CountingRng;
 
 #[test]
 fn test_encode() {
@@ -40,3 +42,40 @@ fn test_encode() {
         ]
     )
 }
+
+#[test]
+fn test_encode_user_property() {
+    let mut buffer: [u8; 100] = [0; 100];
+    let mut config = ClientConfig::<1, _>::new(MqttVersion::MQTTv5, CountingRng(0));
+    config.add_user_property("a", "b");
+    let mut connect = ConnectPacket::<1, 0>::new();
+    connect.property_len = connect.add_properties(&config.properties);
+    let res = connect.encode(&mut buffer, 100);
+
+    assert!(res.is_ok());
+    assert_eq!(
+        buffer[0..res.unwrap()],
+        [
+            0x10, 0x14, 0x00, 0x04, 0x4d, 0x51, 0x54, 0x54, 0x05, 0x02, 0x00, 0xB4, 0x07, 0x26,
+            0x00, 0x01, 0x61, 0x00, 0x01, 0x62, 0x00, 0x00
+        ]
+    )
+}
+
+#[test]
+fn test_encode_without_user_property() {
+    let mut buffer: [u8; 100] = [0; 100];
+    let config = ClientConfig::<1, _>::new(MqttVersion::MQTTv5, CountingRng(0));
+    let mut connect = ConnectPacket::<1, 0>::new();
+    connect.property_len = connect.add_properties(&config.properties);
+    let res = connect.encode(&mut buffer, 100);
+
+    assert!(res.is_ok());
+    assert_eq!(
+        buffer[0..res.unwrap()],
+        [
+            0x10, 0x0D, 0x00, 0x04, 0x4d, 0x51, 0x54, 0x54, 0x05, 0x02, 0x00, 0xB4, 0x00, 0x00,
+            0x00
+        ]
+    )
+}
@@ -28,6 +28,7 @@ use crate::packet::v5::disconnect_packet::DisconnectPacket;
 use crate::packet::v5::mqtt_packet::Packet;
 use crate::packet::v5::packet_type::PacketType;
 use crate::packet::v5::property::Property;
+use crate::packet::v5::reason_codes::ReasonCode;
 use crate::utils::buffer_reader::BuffReader;
 
 #[test]
@@ -56,10 +57,53 @@ fn test_decode() {
     assert_eq!(packet.remain_len, 7);
     assert_eq!(packet.disconnect_reason, 0x00);
     assert_eq!(packet.property_len, 5);
-    let prop = packet.properties.get(0);
+    let prop = packet.properties.first();
     assert!(prop.is_some());
     assert_eq!(<&Property as Into<u8>>::into(prop.unwrap()), 0x11);
     if let Property::SessionExpiryInterval(u) = *prop.unwrap() {
         assert_eq!(u, 1024);
     }
 }
+
+#[test]
+fn test_decode_server_reference() {
+    let buffer: [u8; 15] = [
+        0xE0, 0x0D, 0x9D, 0x0B, 0x1C, 0x00, 0x08, 0x6E, 0x65, 0x77, 0x3A, 0x31, 0x38, 0x38, 0x33,
+    ];
+    let mut packet = DisconnectPacket::<1>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 15));
+    assert!(res.is_ok());
+    let reason = ReasonCode::from(packet.disconnect_reason);
+    assert_eq!(reason, ReasonCode::ServerMoved);
+    assert!(reason.is_redirect());
+    assert_eq!(packet.server_reference(), Some("new:1883"));
+}
+
+#[test]
+fn test_decode_reason_only() {
+    let buffer: [u8; 3] = [0xE0, 0x01, 0x8B];
+    let mut packet = DisconnectPacket::<1>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 3));
+    assert!(res.is_ok());
+    assert_eq!(
+        ReasonCode::from(packet.disconnect_reason),
+        ReasonCode::ServerShuttingDown
+    );
+    assert_eq!(packet.property_len, 0);
+    assert_eq!(packet.server_reference(), None);
+}
+
+#[test]
+fn test_decode_user_properties() {
+    let buffer: [u8; 19] = [
+        0xE0, 0x11, 0x98, 0x0F, 0x26, 0x00, 0x01, 0x61, 0x00, 0x01, 0x31, 0x26, 0x00, 0x01, 0x62,
+        0x00, 0x02, 0x32, 0x33,
+    ];
+    let mut packet = DisconnectPacket::<3>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 19));
+    assert!(res.is_ok());
+    let mut properties = packet.user_properties();
+    assert_eq!(properties.next(), Some(("a", "1")));
+    assert_eq!(properties.next(), Some(("b", "23")));
+    assert_eq!(properties.next(), None);
+}
@@ -28,6 +28,7 @@ use crate::packet::v5::mqtt_packet::Packet;
 use crate::packet::v5::packet_type::PacketType;
 use crate::packet::v5::property::Property;
 use crate::packet::v5::puback_packet::PubackPacket;
+use crate::packet::v5::reason_codes::ReasonCode;
 use crate::utils::buffer_reader::BuffReader;
 use crate::utils::types::EncodedString;
 
@@ -65,10 +66,42 @@ fn test_decode() {
     assert_eq!(packet.packet_identifier, 35422);
     assert_eq!(packet.reason_code, 0x15);
     assert_eq!(packet.property_len, 8);
-    let prop = packet.properties.get(0);
+    let prop = packet.properties.first();
     assert!(prop.is_some());
     assert_eq!(<&Property as Into<u8>>::into(prop.unwrap()), 0x1F);
     if let Property::ReasonString(u) = (*prop.unwrap()).clone() {
         assert_eq!(u.string, "Hello");
     }
 }
+
+#[test]
+fn test_decode_without_reason_code() {
+    let buffer: [u8; 4] = [0x40, 0x02, 0x00, 0x01];
+    let mut packet = PubackPacket::<1>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 4));
+    assert!(res.is_ok());
+    assert_eq!(packet.packet_identifier, 1);
+    assert_eq!(ReasonCode::from(packet.reason_code), ReasonCode::Success);
+}
+
+#[test]
+fn test_decode_no_matching_subscribers() {
+    let buffer: [u8; 5] = [0x40, 0x03, 0x00, 0x01, 0x10];
+    let mut packet = PubackPacket::<1>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 5));
+    assert!(res.is_ok());
+    let reason = ReasonCode::from(packet.reason_code);
+    assert_eq!(reason, ReasonCode::NoMatchingSubscribers);
+    assert!(!reason.is_error());
+}
+
+#[test]
+fn test_decode_rejected() {
+    let buffer: [u8; 5] = [0x40, 0x03, 0x00, 0x01, 0x97];
+    let mut packet = PubackPacket::<1>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 5));
+    assert!(res.is_ok());
+    let reason = ReasonCode::from(packet.reason_code);
+    assert_eq!(reason, ReasonCode::QuotaExceeded);
+    assert!(reason.is_error());
+}
@@ -24,12 +24,12 @@
 
 use heapless::Vec;
 
-use crate::packet::v5::mqtt_packet::Packet;
+use crate::packet::v5::mqtt_packet::{decode_from_slice, encode_to_slice, Packet};
 use crate::packet::v5::packet_type::PacketType;
 use crate::packet::v5::property::Property;
 use crate::packet::v5::publish_packet::{PublishPacket, QualityOfService};
 use crate::utils::buffer_reader::BuffReader;
-use crate::utils::types::EncodedString;
+use crate::utils::types::{BufferError, EncodedString};
 
 #[test]
 fn test_encode() {
@@ -77,7 +77,7 @@ fn test_decode() {
     assert_eq!(packet.topic_name.string, "test");
     assert_eq!(packet.packet_identifier, 23432);
     assert_eq!(packet.property_len, 7);
-    let prop = packet.properties.get(0);
+    let prop = packet.properties.first();
     assert!(prop.is_some());
     assert_eq!(<&Property as Into<u8>>::into(prop.unwrap()), 0x01);
     if let Property::PayloadFormat(u) = (*prop.unwrap()).clone() {
@@ -96,3 +96,152 @@ fn test_decode() {
         );
     }
 }
+
+#[test]
+fn test_decode_subscription_identifiers() {
+    let buffer: [u8; 16] = [
+        0x30, 0x0E, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x05, 0x0B, 0x01, 0x0B, 0x81, 0x01, 0x68,
+        0x69,
+    ];
+    let mut packet = PublishPacket::<3>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 16));
+    assert!(res.is_ok());
+    assert_eq!(packet.property_len, 5);
+    let mut identifiers = packet.subscription_identifiers();
+    assert_eq!(identifiers.next(), Some(1));
+    assert_eq!(identifiers.next(), Some(129));
+    assert_eq!(identifiers.next(), None);
+    assert_eq!(packet.message.unwrap(), b"hi");
+}
+
+#[test]
+fn test_slice_round_trip() {
+    let mut buffer: [u8; 20] = [0; 20];
+    let mut packet = PublishPacket::<2>::new();
+    packet.add_qos(QualityOfService::QoS1);
+    let mut topic = EncodedString::new();
+    topic.string = "test";
+    topic.len = 4;
+    packet.topic_name = topic;
+    packet.packet_identifier = 12;
+    packet.add_message(b"hello");
+    let len = encode_to_slice(&mut packet, &mut buffer);
+    assert_eq!(len, Ok(16));
+
+    let decoded = decode_from_slice::<PublishPacket<2>>(&buffer[..16]).unwrap();
+    assert_eq!(decoded.topic_name.string, "test");
+    assert_eq!(decoded.packet_identifier, 12);
+    assert_eq!(decoded.message, Some(&b"hello"[..]));
+}
+
+#[test]
+fn test_encode_to_slice_small_buffer() {
+    let mut buffer: [u8; 4] = [0; 4];
+    let mut packet = PublishPacket::<2>::new();
+    packet.add_message(b"hello");
+    assert!(encode_to_slice(&mut packet, &mut buffer).is_err());
+}
+
+#[test]
+fn test_message_str() {
+    let buffer: [u8; 13] = [
+        0x30, 0x0B, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x02, 0x01, 0x01, 0x68, 0x69,
+    ];
+    let mut packet = PublishPacket::<2>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 13));
+    assert!(res.is_ok());
+    assert_eq!(packet.message_str(), Some(Ok("hi")));
+}
+
+#[test]
+fn test_message_str_invalid_utf8() {
+    let buffer: [u8; 13] = [
+        0x30, 0x0B, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x02, 0x01, 0x01, 0xFF, 0xFE,
+    ];
+    let mut packet = PublishPacket::<2>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 13));
+    assert!(res.is_ok());
+    assert!(matches!(packet.message_str(), Some(Err(_))));
+    assert_eq!(packet.message.unwrap(), [0xFF, 0xFE]);
+}
+
+#[test]
+fn test_message_str_unspecified_format() {
+    let buffer: [u8; 13] = [
+        0x30, 0x0B, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x02, 0x01, 0x00, 0x68, 0x69,
+    ];
+    let mut packet = PublishPacket::<2>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 13));
+    assert!(res.is_ok());
+    assert_eq!(packet.message_str(), None);
+}
+
+#[test]
+fn test_encoded_len() {
+    let mut buffer: [u8; 100] = [0; 100];
+    for qos in [
+        QualityOfService::QoS0,
+        QualityOfService::QoS1,
+        QualityOfService::QoS2,
+    ] {
+        let mut packet = PublishPacket::<1>::new();
+        packet.add_topic_name("test/topic");
+        packet.add_qos(qos);
+        packet.add_identifier(5);
+        packet.add_message(b"Hello world");
+        let res = packet.encode(&mut buffer, 100);
+        assert_eq!(res, packet.encoded_len());
+        assert_eq!(packet.remaining_len(), Ok(res.unwrap() as u32 - 2));
+    }
+}
+
+#[test]
+fn test_encoded_len_with_properties() {
+    let mut buffer: [u8; 100] = [0; 100];
+    let mut packet = PublishPacket::<2>::new();
+    packet.add_topic_name("test");
+    packet.add_qos(QualityOfService::QoS1);
+    packet.add_identifier(5);
+    packet.add_message(b"hi");
+    let mut props = Vec::<Property, 2>::new();
+    props.push(Property::PayloadFormat(1));
+    props.push(Property::MessageExpiryInterval(60));
+    packet.property_len = packet.add_properties(&props);
+    let res = packet.encode(&mut buffer, 100);
+    assert!(res.is_ok());
+    assert_eq!(res, packet.encoded_len());
+}
+
+#[test]
+fn test_decode_two_byte_subscription_identifier() {
+    let buffer: [u8; 14] = [
+        0x30, 0x0C, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x03, 0x0B, 0xAC, 0x02, 0x68, 0x69,
+    ];
+    let mut packet = PublishPacket::<2>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 14));
+    assert!(res.is_ok());
+    let mut identifiers = packet.subscription_identifiers();
+    assert_eq!(identifiers.next(), Some(300));
+    assert_eq!(identifiers.next(), None);
+    assert_eq!(packet.message.unwrap(), b"hi");
+}
+
+#[test]
+fn test_decode_zero_subscription_identifier() {
+    let buffer: [u8; 13] = [
+        0x30, 0x0B, 0x00, 0x04, 0x74, 0x65, 0x73, 0x74, 0x02, 0x0B, 0x00, 0x68, 0x69,
+    ];
+    let mut packet = PublishPacket::<2>::new();
+    let res = packet.decode(&mut BuffReader::new(&buffer, 13));
+    assert_eq!(res, Err(BufferError::ProtocolError));
+}
+
+#[test]
+fn test_encoded_len_with_message_len() {
+    let mut packet = PublishPacket::<1>::new();
+    packet.add_topic_name("test/topic");
+    packet.add_qos(QualityOfService::QoS1);
+    // 2 + 10 topic, 2 identifier, 1 property length and 200 message bytes
+    assert_eq!(packet.encoded_len_with_message_len(200), Ok(3 + 215));
+    assert_eq!(packet.encoded_len_with_message_len(0), Ok(2 + 15));
+}
@@ -76,7 +76,7 @@ fn test_decode() {
     assert_eq!(packet.packet_identifier, 12345);
     assert_eq!(packet.reason_code, 0x86);
     assert_eq!(packet.property_len, 15);
-    let prop = packet.properties.get(0);
+    let prop = packet.properties.first();
     assert!(prop.is_some());
     assert_eq!(<&Property as Into<u8>>::into(prop.unwrap()), 0x26);
     if let Property::UserProperty(u) = (*prop.unwrap()).clone() {
@@ -0,0 +1,223 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::packet::v5::publish_packet::QualityOfService;
+use crate::packet::v5::reason_codes::ReasonCode;
+use crate::packet::v5::reason_codes::ReasonCodeCategory::{
+    ClientError, ServerError, Success, Transport, Warning,
+};
+
+#[test]
+fn test_category() {
+    let codes = [
+        (0x00, Success),
+        (0x01, Success),
+        (0x02, Success),
+        (0x04, Success),
+        (0x10, Warning),
+        (0x11, Warning),
+        (0x18, Success),
+        (0x19, Success),
+        (0x80, ServerError),
+        (0x81, ClientError),
+        (0x82, ClientError),
+        (0x83, ServerError),
+        (0x84, ClientError),
+        (0x85, ClientError),
+        (0x86, ClientError),
+        (0x87, ClientError),
+        (0x88, ServerError),
+        (0x89, ServerError),
+        (0x8A, ClientError),
+        (0x8B, ServerError),
+        (0x8C, ClientError),
+        (0x8D, ClientError),
+        (0x8E, ServerError),
+        (0x8F, ClientError),
+        (0x90, ClientError),
+        (0x91, ClientError),
+        (0x92, ClientError),
+        (0x93, ClientError),
+        (0x94, ClientError),
+        (0x95, ClientError),
+        (0x96, ClientError),
+        (0x97, ServerError),
+        (0x98, ServerError),
+        (0x99, ClientError),
+        (0x9A, ServerError),
+        (0x9B, ServerError),
+        (0x9C, ServerError),
+        (0x9D, ServerError),
+        (0x9E, ServerError),
+        (0x9F, ServerError),
+        (0xA0, ServerError),
+        (0xA1, ServerError),
+        (0xA2, ServerError),
+        (0xFC, ClientError),
+        (0xFD, ClientError),
+        (0xFE, ClientError),
+        (0xFF, Transport),
+    ];
+    for (value, category) in codes {
+        let code = ReasonCode::from(value);
+        assert_eq!(code.category(), category);
+        assert_eq!(code.is_success(), category == Success);
+        assert_eq!(code.is_normal(), category == Success || category == Warning);
+        assert_eq!(code.is_error(), value >= 0x80);
+        assert_eq!(u8::from(code), value);
+    }
+}
+
+#[test]
+fn test_display() {
+    extern crate std;
+    use std::string::ToString;
+
+    assert_eq!(ReasonCode::QuotaExceeded.to_string(), "Quota exceeded!");
+    assert_eq!(
+        ReasonCode::from(0x9F).to_string(),
+        "Connection rate exceeded!"
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_error() {
+    extern crate std;
+    use std::boxed::Box;
+    use std::error::Error;
+    use std::string::ToString;
+
+    let err: Box<dyn Error> = Box::new(ReasonCode::NotAuthorized);
+    assert_eq!(err.to_string(), "Client not authorized!");
+}
+
+#[test]
+fn test_is_redirect() {
+    assert!(ReasonCode::UseAnotherServer.is_redirect());
+    assert!(ReasonCode::ServerMoved.is_redirect());
+    assert!(!ReasonCode::ServerShuttingDown.is_redirect());
+    assert!(!ReasonCode::Success.is_redirect());
+}
+
+#[test]
+fn test_allowed_in_client_disconnect() {
+    let codes = [
+        (0x00, true),
+        (0x01, false),
+        (0x02, false),
+        (0x04, true),
+        (0x10, false),
+        (0x11, false),
+        (0x18, false),
+        (0x19, false),
+        (0x80, true),
+        (0x81, true),
+        (0x82, true),
+        (0x83, true),
+        (0x84, false),
+        (0x85, false),
+        (0x86, false),
+        (0x87, false),
+        (0x88, false),
+        (0x89, false),
+        (0x8A, false),
+        (0x8B, false),
+        (0x8C, false),
+        (0x8D, false),
+        (0x8E, false),
+        (0x8F, false),
+        (0x90, true),
+        (0x91, false),
+        (0x92, false),
+        (0x93, true),
+        (0x94, true),
+        (0x95, true),
+        (0x96, true),
+        (0x97, true),
+        (0x98, true),
+        (0x99, true),
+        (0x9A, false),
+        (0x9B, false),
+        (0x9C, false),
+        (0x9D, false),
+        (0x9E, false),
+        (0x9F, false),
+        (0xA0, false),
+        (0xA1, false),
+        (0xA2, false),
+        (0xFC, false),
+        (0xFD, false),
+        (0xFE, false),
+        (0xFF, false),
+    ];
+    for (value, allowed) in codes {
+        assert_eq!(
+            ReasonCode::from(value).allowed_in_client_disconnect(),
+            allowed,
+            "reason code {:#04x}",
+            value
+        );
+    }
+}
+
+#[test]
+fn test_is_retryable() {
+    // Reason codes which can be received in PUBACK
+    let codes = [
+        (ReasonCode::Success, false),
+        (ReasonCode::NoMatchingSubscribers, false),
+        (ReasonCode::UnspecifiedError, false),
+        (ReasonCode::ImplementationSpecificError, true),
+        (ReasonCode::NotAuthorized, false),
+        (ReasonCode::TopicNameInvalid, false),
+        (ReasonCode::PacketIdentifierInUse, true),
+        (ReasonCode::QuotaExceeded, true),
+        (ReasonCode::PayloadFormatInvalid, false),
+    ];
+    for (code, retryable) in codes {
+        assert_eq!(code.is_retryable(), retryable);
+    }
+}
+
+#[test]
+fn test_granted_qos() {
+    let codes = [
+        (0x00, Some(QualityOfService::QoS0)),
+        (0x01, Some(QualityOfService::QoS1)),
+        (0x02, Some(QualityOfService::QoS2)),
+        (0x80, None),
+        (0x83, None),
+        (0x87, None),
+        (0x8F, None),
+        (0x91, None),
+        (0x97, None),
+        (0x9E, None),
+        (0xA1, None),
+        (0xA2, None),
+    ];
+    for (value, qos) in codes {
+        assert_eq!(ReasonCode::from(value).granted_qos(), qos);
+    }
+}
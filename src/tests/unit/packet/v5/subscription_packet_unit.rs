@@ -28,7 +28,7 @@ use crate::packet::v5::mqtt_packet::Packet;
 use crate::packet::v5::packet_type::PacketType;
 use crate::packet::v5::property::Property;
 use crate::packet::v5::publish_packet::QualityOfService::{QoS0, QoS1};
-use crate::packet::v5::subscription_packet::SubscriptionPacket;
+use crate::packet::v5::subscription_packet::{RetainHandling, SubscriptionPacket};
 
 #[test]
 fn test_encode() {
@@ -53,3 +53,55 @@ fn test_encode() {
         ]
     );
 }
+
+#[test]
+fn test_encode_retain_handling() {
+    let mut buffer: [u8; 30] = [0; 30];
+    let mut packet = SubscriptionPacket::<3, 1>::new();
+    packet.packet_identifier = 1;
+    packet.add_new_filter_with_retain_handling("a", QoS1, RetainHandling::AlwaysSend);
+    packet.add_new_filter_with_retain_handling(
+        "b",
+        QoS1,
+        RetainHandling::SendIfSubscriptionDoesNotExist,
+    );
+    packet.add_new_filter_with_retain_handling("c", QoS0, RetainHandling::DoNotSend);
+    let res = packet.encode(&mut buffer, 30);
+    assert!(res.is_ok());
+    assert_eq!(
+        buffer[0..res.unwrap()],
+        [
+            0x82, 0x0F, 0x00, 0x01, 0x00, 0x00, 0x01, 0x61, 0x01, 0x00, 0x01, 0x62, 0x11, 0x00,
+            0x01, 0x63, 0x20
+        ]
+    );
+}
+
+#[test]
+fn test_encoded_len() {
+    let mut buffer: [u8; 30] = [0; 30];
+    let mut packet = SubscriptionPacket::<2, 1>::new();
+    let mut props = Vec::<Property, 2>::new();
+    props.push(Property::SubscriptionIdentifier(2432));
+    packet.property_len = packet.add_properties(&props);
+    packet.add_new_filter("test/topic", QoS0);
+    packet.add_new_filter("hehe/#", QoS1);
+    assert_eq!(packet.remaining_len(), Ok(28));
+    assert_eq!(packet.encoded_len(), Ok(30));
+    let res = packet.encode(&mut buffer, 30);
+    assert_eq!(res, packet.encoded_len());
+}
+
+#[test]
+fn test_encoded_len_long_packet() {
+    let mut buffer: [u8; 200] = [0; 200];
+    let topic =
+        "a/very/long/topic/filter/which/needs/two/bytes/of/remaining/length/when/used/twice";
+    let mut packet = SubscriptionPacket::<2, 1>::new();
+    packet.add_new_filter(topic, QoS0);
+    packet.add_new_filter(topic, QoS1);
+    let res = packet.encode(&mut buffer, 200);
+    assert!(res.is_ok());
+    assert_eq!(res, packet.encoded_len());
+    assert_eq!(packet.remaining_len(), Ok(res.unwrap() as u32 - 3));
+}
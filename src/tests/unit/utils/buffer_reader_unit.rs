@@ -233,3 +233,50 @@ fn test_read_string_pair_oob() {
         BufferError::InsufficientBufferSize
     );
 }
+
+#[test]
+fn buffer_read_variable_byte_too_long() {
+    static BUFFER: [u8; 5] = [0x80, 0x80, 0x80, 0x80, 0x01];
+    let mut reader: BuffReader = BuffReader::new(&BUFFER, 5);
+    let test_number = reader.read_variable_byte_int();
+    assert!(test_number.is_err());
+    assert_eq!(test_number.unwrap_err(), BufferError::DecodingError);
+}
+
+#[test]
+fn test_read_i32() {
+    static BUFFER: [u8; 4] = [0xFF, 0xFD, 0xA1, 0x3F];
+    let mut reader: BuffReader = BuffReader::new(&BUFFER, 4);
+    let test_number = reader.read_i32();
+    assert!(test_number.is_ok());
+    assert_eq!(test_number.unwrap(), -155329);
+}
+
+#[test]
+fn test_read_i16() {
+    static BUFFER: [u8; 2] = [0x80, 0x00];
+    let mut reader: BuffReader = BuffReader::new(&BUFFER, 2);
+    let test_number = reader.read_i16();
+    assert!(test_number.is_ok());
+    assert_eq!(test_number.unwrap(), i16::MIN);
+}
+
+#[test]
+fn test_read_i8() {
+    static BUFFER: [u8; 1] = [0xFF];
+    let mut reader: BuffReader = BuffReader::new(&BUFFER, 1);
+    let test_number = reader.read_i8();
+    assert!(test_number.is_ok());
+    assert_eq!(test_number.unwrap(), -1);
+}
+
+#[test]
+fn test_read_i32_oob() {
+    static BUFFER: [u8; 3] = [0xFF, 0xFD, 0xA1];
+    let mut reader: BuffReader = BuffReader::new(&BUFFER, 3);
+    let test_number = reader.read_i32();
+    assert_eq!(
+        test_number.unwrap_err(),
+        BufferError::InsufficientBufferSize
+    );
+}
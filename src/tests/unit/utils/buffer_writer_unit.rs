@@ -27,6 +27,7 @@ use tokio_test::{assert_err, assert_ok};
 
 use crate::encoding::variable_byte_integer::VariableByteInteger;
 use crate::packet::v5::property::Property;
+use crate::utils::buffer_reader::BuffReader;
 use crate::utils::buffer_writer::BuffWriter;
 use crate::utils::types::{BinaryData, BufferError, EncodedString, StringPair, TopicFilter};
 
@@ -497,3 +498,30 @@ fn buffer_get_rem_len_cont() {
     assert_ok!(rm_len_sec);
     assert_eq!(rm_len_sec.unwrap(), [0x81, 0x82, 0x01, 0x00]);
 }
+
+#[test]
+fn buffer_write_signed_round_trip() {
+    let mut res_buffer: [u8; 7] = [0; 7];
+
+    let mut writer: BuffWriter = BuffWriter::new(&mut res_buffer, 7);
+    assert!(writer.write_i32(-155329).is_ok());
+    assert!(writer.write_i16(i16::MIN).is_ok());
+    assert!(writer.write_i8(-1).is_ok());
+    assert_eq!(writer.position, 7);
+    assert_eq!(res_buffer, [0xFF, 0xFD, 0xA1, 0x3F, 0x80, 0x00, 0xFF]);
+
+    let mut reader: BuffReader = BuffReader::new(&res_buffer, 7);
+    assert_eq!(reader.read_i32(), Ok(-155329));
+    assert_eq!(reader.read_i16(), Ok(i16::MIN));
+    assert_eq!(reader.read_i8(), Ok(-1));
+}
+
+#[test]
+fn buffer_write_i16_oob() {
+    let mut res_buffer: [u8; 1] = [0; 1];
+
+    let mut writer: BuffWriter = BuffWriter::new(&mut res_buffer, 1);
+    let test_write = writer.write_i16(-2);
+    assert!(test_write.is_err());
+    assert_eq!(test_write.unwrap_err(), BufferError::InsufficientBufferSize);
+}
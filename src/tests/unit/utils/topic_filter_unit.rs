@@ -0,0 +1,93 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use heapless::{String, Vec};
+
+use crate::packet::v5::reason_codes::ReasonCode;
+use crate::utils::topic_filter;
+
+#[test]
+fn test_shared() {
+    let res: Result<String<32>, ReasonCode> = topic_filter::shared("group", "sensors/+/temp");
+    let filter = res.unwrap();
+    assert_eq!(filter.as_str(), "$share/group/sensors/+/temp");
+    assert!(topic_filter::is_shared(&filter));
+    assert!(topic_filter::is_valid_shared(&filter));
+}
+
+#[test]
+fn test_shared_invalid_group() {
+    for group in ["", "a/b", "a+", "#"] {
+        let res: Result<String<32>, ReasonCode> = topic_filter::shared(group, "test");
+        assert_eq!(res, Err(ReasonCode::TopicFilterInvalid));
+    }
+}
+
+#[test]
+fn test_shared_too_long() {
+    let res: Result<String<8>, ReasonCode> = topic_filter::shared("group", "test");
+    assert_eq!(res, Err(ReasonCode::BuffError));
+}
+
+#[test]
+fn test_is_valid_shared() {
+    assert!(topic_filter::is_valid_shared("test/topic"));
+    assert!(!topic_filter::is_shared("test/topic"));
+    assert!(!topic_filter::is_valid_shared("$share/group"));
+    assert!(!topic_filter::is_valid_shared("$share//test"));
+    assert!(!topic_filter::is_valid_shared("$share/gr+oup/test"));
+    assert!(!topic_filter::is_valid_shared("$share/group/"));
+}
+
+#[test]
+fn test_has_wildcards() {
+    assert!(topic_filter::has_wildcards("sensors/+/temp"));
+    assert!(topic_filter::has_wildcards("sensors/#"));
+    assert!(topic_filter::has_wildcards("$share/group/#"));
+    assert!(!topic_filter::has_wildcards("sensors/kitchen/temp"));
+}
+
+#[test]
+fn test_levels() {
+    let levels: Vec<&str, 4> = topic_filter::levels("a/b/c").collect();
+    assert_eq!(levels, ["a", "b", "c"]);
+}
+
+#[test]
+fn test_levels_leading_separator() {
+    let levels: Vec<&str, 4> = topic_filter::levels("/a").collect();
+    assert_eq!(levels, ["", "a"]);
+}
+
+#[test]
+fn test_levels_empty_level() {
+    let levels: Vec<&str, 4> = topic_filter::levels("a//b").collect();
+    assert_eq!(levels, ["a", "", "b"]);
+}
+
+#[test]
+fn test_levels_single_level() {
+    let levels: Vec<&str, 4> = topic_filter::levels("sport").collect();
+    assert_eq!(levels, ["sport"]);
+}
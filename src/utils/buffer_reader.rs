@@ -119,6 +119,21 @@ impl<'a> BuffReader<'a> {
         Ok(ret)
     }
 
+    /// Reading i32 from buffer as `Big endian` two's complement
+    pub fn read_i32(&mut self) -> Result<i32, BufferError> {
+        Ok(self.read_u32()? as i32)
+    }
+
+    /// Reading i16 from buffer as `Big endian` two's complement
+    pub fn read_i16(&mut self) -> Result<i16, BufferError> {
+        Ok(self.read_u16()? as i16)
+    }
+
+    /// Reading one byte from buffer as two's complement
+    pub fn read_i8(&mut self) -> Result<i8, BufferError> {
+        Ok(self.read_u8()? as i8)
+    }
+
     /// Reading UTF-8 encoded string from buffer
     pub fn read_string(&mut self) -> Result<EncodedString<'a>, BufferError> {
         let len = self.read_u16()? as usize;
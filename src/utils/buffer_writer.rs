@@ -118,6 +118,21 @@ impl<'a> BuffWriter<'a> {
         self.insert_ref(4, &bytes)
     }
 
+    /// Writes a single signed Byte to the buffer.
+    pub fn write_i8(&mut self, byte: i8) -> Result<(), BufferError> {
+        self.write_u8(byte as u8)
+    }
+
+    /// Writes the two Byte signed value to the buffer.
+    pub fn write_i16(&mut self, two_bytes: i16) -> Result<(), BufferError> {
+        self.write_u16(two_bytes as u16)
+    }
+
+    /// Writes the four Byte signed value to the buffer.
+    pub fn write_i32(&mut self, four_bytes: i32) -> Result<(), BufferError> {
+        self.write_u32(four_bytes as u32)
+    }
+
     /// Writes the UTF-8 string type to the buffer.
     pub fn write_string_ref(&mut self, str: &EncodedString<'a>) -> Result<(), BufferError> {
         self.write_u16(str.len)?;
@@ -25,4 +25,5 @@
 pub mod buffer_reader;
 pub mod buffer_writer;
 pub mod rng_generator;
+pub mod topic_filter;
 pub mod types;
@@ -0,0 +1,79 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use heapless::String;
+
+use crate::packet::v5::reason_codes::ReasonCode;
+
+const SHARED_PREFIX: &str = "$share/";
+
+/// Builds shared subscription topic filter in the form `$share/{group}/{filter}`.
+/// Share name `group` must not be empty and must not contain `/`, `+` or `#`.
+/// Returns `TopicFilterInvalid` if the group is not valid and `BuffError` if the
+/// result does not fit into `N` bytes.
+pub fn shared<const N: usize>(group: &str, filter: &str) -> Result<String<N>, ReasonCode> {
+    if !is_valid_share_name(group) || filter.is_empty() {
+        return Err(ReasonCode::TopicFilterInvalid);
+    }
+    let mut shared = String::new();
+    shared
+        .push_str(SHARED_PREFIX)
+        .and_then(|_| shared.push_str(group))
+        .and_then(|_| shared.push('/'))
+        .and_then(|_| shared.push_str(filter))
+        .map_err(|_| ReasonCode::BuffError)?;
+    Ok(shared)
+}
+
+/// Returns true if the `filter` is a shared subscription topic filter.
+pub fn is_shared(filter: &str) -> bool {
+    filter.starts_with(SHARED_PREFIX)
+}
+
+/// Returns true if the `filter` is not a shared subscription or is a valid one.
+pub fn is_valid_shared(filter: &str) -> bool {
+    if !is_shared(filter) {
+        return true;
+    }
+    match filter[SHARED_PREFIX.len()..].split_once('/') {
+        Some((group, filter)) => is_valid_share_name(group) && !filter.is_empty(),
+        None => false,
+    }
+}
+
+/// Returns true if the `filter` contains single level `+` or multi level `#` wildcard.
+pub fn has_wildcards(filter: &str) -> bool {
+    filter.contains(['+', '#'])
+}
+
+/// Returns iterator over the levels of the topic name or topic filter. Levels are
+/// separated by `/` and can be empty, so `/a` yields an empty level followed by `a`
+/// and `a//b` yields `a`, an empty level and `b`.
+pub fn levels(topic: &str) -> impl Iterator<Item = &str> {
+    topic.split('/')
+}
+
+fn is_valid_share_name(group: &str) -> bool {
+    !group.is_empty() && !group.contains(['/', '+', '#'])
+}
@@ -37,6 +37,7 @@ pub enum BufferError {
     WrongPacketToDecode,
     WrongPacketToEncode,
     PropertyNotFound,
+    ProtocolError,
 }
 
 impl Display for BufferError {
@@ -51,7 +52,8 @@ impl Display for BufferError {
             BufferError::PacketTypeMismatch => write!(f, "Packet type not matched during decoding (Received different packet type than encode type)!"),
             BufferError::WrongPacketToDecode => write!(f, "Not able to decode packet, this packet is used just for sending to broker, not receiving by client!"),
             BufferError::WrongPacketToEncode => write!(f, "Not able to encode packet, this packet is used only from server to client not the opposite way!"),
-            BufferError::PropertyNotFound => write!(f, "Property with ID not found!")
+            BufferError::PropertyNotFound => write!(f, "Property with ID not found!"),
+            BufferError::ProtocolError => write!(f, "Decoded value is not allowed by the protocol!")
         }
     }
 }
@@ -31,18 +31,19 @@ use futures::future::{join, join3};
 use heapless::Vec;
 use log::info;
 use std::net::{Ipv4Addr, SocketAddr};
-use tokio::time::sleep;
+use tokio::time::{sleep, timeout};
 use tokio::{net::TcpStream, task};
 use tokio_test::{assert_err, assert_ok};
 
 use embedded_io_adapters::tokio_1::FromTokio;
 use rust_mqtt::client::client::MqttClient;
 use rust_mqtt::client::client_config::ClientConfig;
-use rust_mqtt::client::client_config::MqttVersion::MQTTv5;
+use rust_mqtt::client::client_config::MqttVersion::{MQTTv3, MQTTv5};
 use rust_mqtt::packet::v5::property::Property;
 use rust_mqtt::packet::v5::publish_packet::QualityOfService;
 use rust_mqtt::packet::v5::reason_codes::ReasonCode;
 use rust_mqtt::packet::v5::reason_codes::ReasonCode::NotAuthorized;
+use rust_mqtt::packet::v5::subscription_packet::RetainHandling;
 use rust_mqtt::utils::rng_generator::CountingRng;
 pub type TokioNetwork = FromTokio<TcpStream>;
 
@@ -72,8 +73,10 @@ async fn publish_core<'b>(
         "[Publisher] Connection to broker with username {} and password {}",
         USERNAME, PASSWORD
     );
+    assert!(!client.is_connected());
     let mut result = { client.connect_to_broker().await };
     assert_ok!(result);
+    assert!(client.is_connected());
     info!("[Publisher] Waiting {} seconds before sending", wait);
     sleep(Duration::from_secs(wait)).await;
 
@@ -95,6 +98,7 @@ async fn publish_core<'b>(
     result = client.disconnect().await;
 
     assert_ok!(result);
+    assert!(!client.is_connected());
     Ok(())
 }
 
@@ -160,6 +164,35 @@ async fn publish_spec(
     publish_core(&mut client, wait, qos, topic, message, err).await
 }
 
+async fn publish_v3(
+    ip: Ipv4Addr,
+    wait: u64,
+    qos: QualityOfService,
+    topic: &str,
+) -> Result<(), ReasonCode> {
+    let addr = SocketAddr::new(ip.into(), PORT);
+    let connection = TcpStream::connect(addr)
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    let connection = TokioNetwork::new(connection);
+    let mut config = ClientConfig::new(MQTTv3, CountingRng(20000));
+    config.add_max_subscribe_qos(qos);
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    let mut recv_buffer = [0; 80];
+    let mut write_buffer = [0; 80];
+
+    let mut client = MqttClient::<TokioNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        80,
+        &mut recv_buffer,
+        80,
+        config,
+    );
+    publish_core(&mut client, wait, qos, topic, MSG, false).await
+}
+
 async fn receive_core<'b>(
     client: &mut MqttClient<'b, TokioNetwork, 5, CountingRng>,
     topic: &str,
@@ -201,7 +234,7 @@ async fn receive_core_multiple<'b, const TOPICS: usize>(
 
     info!(
         "[Receiver] Subscribing to topics {}, {}",
-        topic_names.get(0).unwrap(),
+        topic_names.first().unwrap(),
         topic_names.get(1).unwrap()
     );
     result = client.subscribe_to_topics(topic_names).await;
@@ -286,6 +319,31 @@ async fn receive(ip: Ipv4Addr, qos: QualityOfService, topic: &str) -> Result<(),
     receive_core(&mut client, topic).await
 }
 
+async fn receive_v3(ip: Ipv4Addr, qos: QualityOfService, topic: &str) -> Result<(), ReasonCode> {
+    let addr = SocketAddr::new(ip.into(), PORT);
+    let connection = TcpStream::connect(addr)
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    let connection = TokioNetwork::new(connection);
+    let mut config = ClientConfig::new(MQTTv3, CountingRng(20000));
+    config.add_max_subscribe_qos(qos);
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    let mut recv_buffer = [0; 100];
+    let mut write_buffer = [0; 100];
+
+    let mut client = MqttClient::<TokioNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        100,
+        &mut recv_buffer,
+        100,
+        config,
+    );
+
+    receive_core(&mut client, topic).await
+}
+
 async fn receive_with_wrong_cred(qos: QualityOfService) -> Result<(), ReasonCode> {
     let addr = SocketAddr::new(IP.into(), PORT);
     let connection = TcpStream::connect(addr)
@@ -358,7 +416,7 @@ async fn receive_multiple_second_unsub<const TOPICS: usize>(
 
     info!(
         "[Receiver] Subscribing to topics {}, {}",
-        topic_names.get(0).unwrap(),
+        topic_names.first().unwrap(),
         topic_names.get(1).unwrap()
     );
     result = client.subscribe_to_topics(topic_names).await;
@@ -405,6 +463,146 @@ async fn receive_multiple_second_unsub<const TOPICS: usize>(
     Ok(())
 }
 
+async fn publish_retained(topic: &str, message: &str) -> Result<(), ReasonCode> {
+    let addr = SocketAddr::new(IP.into(), PORT);
+    let connection = TcpStream::connect(addr)
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    let connection = TokioNetwork::new(connection);
+    let mut config = ClientConfig::new(MQTTv5, CountingRng(20000));
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    let mut recv_buffer = [0; 80];
+    let mut write_buffer = [0; 80];
+
+    let mut client = MqttClient::<TokioNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        80,
+        &mut recv_buffer,
+        80,
+        config,
+    );
+    client.connect_to_broker().await?;
+    client
+        .send_message(topic, message.as_bytes(), QualityOfService::QoS1, true)
+        .await?;
+    client.disconnect().await
+}
+
+async fn receive_retained_once(topic: &str) -> Result<(), ReasonCode> {
+    let addr = SocketAddr::new(IP.into(), PORT);
+    let connection = TcpStream::connect(addr)
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    let connection = TokioNetwork::new(connection);
+    let mut config = ClientConfig::new(MQTTv5, CountingRng(20000));
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    config.add_retain_handling(RetainHandling::SendIfSubscriptionDoesNotExist);
+    let mut recv_buffer = [0; 80];
+    let mut write_buffer = [0; 80];
+
+    let mut client = MqttClient::<TokioNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        80,
+        &mut recv_buffer,
+        80,
+        config,
+    );
+    assert_ok!(client.connect_to_broker().await);
+
+    info!("[Receiver] Subscribing to topic {}", topic);
+    assert_ok!(client.subscribe_to_topic(topic).await);
+    {
+        let msg = client.receive_message().await;
+        assert_ok!(msg);
+        assert_eq!(String::from_utf8_lossy(msg?.1), MSG);
+    }
+
+    info!("[Receiver] Subscribing again, retained message must not be sent");
+    assert_ok!(client.subscribe_to_topic(topic).await);
+    let msg = timeout(Duration::from_secs(2), client.receive_message()).await;
+    assert!(msg.is_err());
+
+    client.disconnect().await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn integration_retain_handling() {
+    setup();
+    info!("Running retain handling test");
+    let topic = "retain/handling";
+    assert_ok!(publish_retained(topic, MSG).await);
+    let res = receive_retained_once(topic).await;
+    // Remove the retained message
+    assert_ok!(publish_retained(topic, "").await);
+    assert_ok!(res);
+}
+
+async fn clear_retained(topic: &str) -> Result<(), ReasonCode> {
+    let addr = SocketAddr::new(IP.into(), PORT);
+    let connection = TcpStream::connect(addr)
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    let connection = TokioNetwork::new(connection);
+    let mut config = ClientConfig::new(MQTTv5, CountingRng(20000));
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    let mut recv_buffer = [0; 80];
+    let mut write_buffer = [0; 80];
+
+    let mut client = MqttClient::<TokioNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        80,
+        &mut recv_buffer,
+        80,
+        config,
+    );
+    client.connect_to_broker().await?;
+    client.clear_retained(topic, QualityOfService::QoS1).await?;
+    client.disconnect().await
+}
+
+async fn receive_nothing(topic: &str) -> Result<(), ReasonCode> {
+    let addr = SocketAddr::new(IP.into(), PORT);
+    let connection = TcpStream::connect(addr)
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    let connection = TokioNetwork::new(connection);
+    let mut config = ClientConfig::new(MQTTv5, CountingRng(20000));
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    let mut recv_buffer = [0; 80];
+    let mut write_buffer = [0; 80];
+
+    let mut client = MqttClient::<TokioNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        80,
+        &mut recv_buffer,
+        80,
+        config,
+    );
+    assert_ok!(client.connect_to_broker().await);
+    assert_ok!(client.subscribe_to_topic(topic).await);
+    let msg = timeout(Duration::from_secs(2), client.receive_message()).await;
+    assert!(msg.is_err());
+    client.disconnect().await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn integration_clear_retained() {
+    setup();
+    info!("Running clear retained test");
+    let topic = "retain/clear";
+    assert_ok!(publish_retained(topic, MSG).await);
+    assert_ok!(clear_retained(topic).await);
+    assert_ok!(receive_nothing(topic).await);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn integration_publish_recv() {
     setup();
@@ -482,6 +680,21 @@ async fn integration_publish_recv_qos() {
     assert_ok!(p.unwrap());
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn integration_publish_recv_qos_v3() {
+    setup();
+    info!("Running tests test with MQTTv3 and Quality of Service 1");
+
+    let recv =
+        task::spawn(async move { receive_v3(IP, QualityOfService::QoS1, "test/recv/v3").await });
+
+    let publ =
+        task::spawn(async move { publish_v3(IP, 5, QualityOfService::QoS1, "test/recv/v3").await });
+    let (r, p) = join(recv, publ).await;
+    assert_ok!(r.unwrap());
+    assert_ok!(p.unwrap());
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn integration_publish_recv_wrong_cred() {
     setup();
@@ -534,3 +747,94 @@ async fn integration_sub_unsub() {
     assert_ok!(p1.unwrap());
     assert_ok!(p2.unwrap());
 }
+
+async fn will_client(topic: &str, trigger_will: bool) -> Result<(), ReasonCode> {
+    let addr = SocketAddr::new(IP.into(), PORT);
+    let connection = TcpStream::connect(addr)
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    let connection = TokioNetwork::new(connection);
+    let mut config = ClientConfig::new(MQTTv5, CountingRng(20000));
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    config.add_will(topic, MSG.as_bytes(), false);
+    let mut recv_buffer = [0; 80];
+    let mut write_buffer = [0; 80];
+
+    let mut client = MqttClient::<TokioNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        80,
+        &mut recv_buffer,
+        80,
+        config,
+    );
+    client.connect_to_broker().await?;
+    // Receiver has to subscribe before the will is published
+    sleep(Duration::from_secs(2)).await;
+    if trigger_will {
+        info!("[Will] Disconnecting with will message");
+        client.disconnect_with_will().await
+    } else {
+        info!("[Will] Disconnecting normally");
+        client.disconnect().await
+    }
+}
+
+async fn receive_will(topic: &str, expect_will: bool) -> Result<(), ReasonCode> {
+    let addr = SocketAddr::new(IP.into(), PORT);
+    let connection = TcpStream::connect(addr)
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    let connection = TokioNetwork::new(connection);
+    let mut config = ClientConfig::new(MQTTv5, CountingRng(20000));
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    let mut recv_buffer = [0; 80];
+    let mut write_buffer = [0; 80];
+
+    let mut client = MqttClient::<TokioNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        80,
+        &mut recv_buffer,
+        80,
+        config,
+    );
+    assert_ok!(client.connect_to_broker().await);
+    assert_ok!(client.subscribe_to_topic(topic).await);
+    {
+        let msg = timeout(Duration::from_secs(5), client.receive_message()).await;
+        if expect_will {
+            let (_, message) = msg.expect("Will message was not received")?;
+            assert_eq!(message, MSG.as_bytes());
+        } else {
+            assert!(msg.is_err());
+        }
+    }
+    client.disconnect().await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn integration_disconnect_with_will() {
+    setup();
+    info!("Running disconnect with will test");
+    let recv = task::spawn(async move { receive_will("will/trigger", true).await });
+    let will = task::spawn(async move { will_client("will/trigger", true).await });
+
+    let (r, w) = join(recv, will).await;
+    assert_ok!(r.unwrap());
+    assert_ok!(w.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn integration_disconnect_suppresses_will() {
+    setup();
+    info!("Running disconnect without will test");
+    let recv = task::spawn(async move { receive_will("will/suppress", false).await });
+    let will = task::spawn(async move { will_client("will/suppress", false).await });
+
+    let (r, w) = join(recv, will).await;
+    assert_ok!(r.unwrap());
+    assert_ok!(w.unwrap());
+}
@@ -0,0 +1,151 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) [2022] [Ondrej Babec <ond.babec@gmail.com>]
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in all
+ * copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+#![cfg(feature = "websocket")]
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Once;
+
+use futures::future::join;
+use log::info;
+use tokio::time::{sleep, Duration};
+use tokio::{net::TcpStream, task};
+use tokio_test::assert_ok;
+
+use embedded_io_adapters::tokio_1::FromTokio;
+use rust_mqtt::client::client::MqttClient;
+use rust_mqtt::client::client_config::ClientConfig;
+use rust_mqtt::client::client_config::MqttVersion::MQTTv5;
+use rust_mqtt::network::websocket::WebSocketConnection;
+use rust_mqtt::packet::v5::publish_packet::QualityOfService;
+use rust_mqtt::packet::v5::reason_codes::ReasonCode;
+use rust_mqtt::utils::rng_generator::CountingRng;
+
+pub type WebSocketNetwork = WebSocketConnection<FromTokio<TcpStream>, CountingRng>;
+
+static IP: Ipv4Addr = Ipv4Addr::new(127, 0, 0, 1);
+static WS_PORT: u16 = 8080;
+static USERNAME: &str = "test";
+static PASSWORD: &str = "testPass";
+static MSG: &str = "testMessage";
+
+static INIT: Once = Once::new();
+
+fn setup() {
+    INIT.call_once(|| {
+        env_logger::init();
+    });
+}
+
+async fn connect_ws() -> Result<WebSocketNetwork, ReasonCode> {
+    let addr = SocketAddr::new(IP.into(), WS_PORT);
+    let connection = TcpStream::connect(addr)
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    let mut ws = WebSocketConnection::new(FromTokio::new(connection), CountingRng(1000));
+    ws.connect("localhost", "/mqtt")
+        .await
+        .map_err(|_| ReasonCode::NetworkError)?;
+    Ok(ws)
+}
+
+async fn publish_ws(wait: u64, qos: QualityOfService, topic: &str) -> Result<(), ReasonCode> {
+    let connection = connect_ws().await?;
+    let mut config = ClientConfig::new(MQTTv5, CountingRng(20000));
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    let mut recv_buffer = [0; 80];
+    let mut write_buffer = [0; 80];
+    let mut client = MqttClient::<WebSocketNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        80,
+        &mut recv_buffer,
+        80,
+        config,
+    );
+
+    info!("[Publisher] Connecting over WebSocket");
+    client.connect_to_broker().await?;
+    sleep(Duration::from_secs(wait)).await;
+    info!("[Publisher] Sending message to topic {}", topic);
+    client
+        .send_message(topic, MSG.as_bytes(), qos, false)
+        .await?;
+    client.disconnect().await
+}
+
+async fn receive_ws(qos: QualityOfService, topic: &str) -> Result<(), ReasonCode> {
+    let connection = connect_ws().await?;
+    let mut config = ClientConfig::new(MQTTv5, CountingRng(30000));
+    config.add_max_subscribe_qos(qos);
+    config.add_username(USERNAME);
+    config.add_password(PASSWORD);
+    let mut recv_buffer = [0; 100];
+    let mut write_buffer = [0; 100];
+    let mut client = MqttClient::<WebSocketNetwork, 5, CountingRng>::new(
+        connection,
+        &mut write_buffer,
+        100,
+        &mut recv_buffer,
+        100,
+        config,
+    );
+
+    info!("[Receiver] Connecting over WebSocket");
+    client.connect_to_broker().await?;
+    client.subscribe_to_topic(topic).await?;
+    info!("[Receiver] Waiting for new message!");
+    let (_, message) = client.receive_message().await?;
+    assert_eq!(message, MSG.as_bytes());
+    client.disconnect().await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn integration_websocket_publish_recv() {
+    setup();
+    info!("Running WebSocket test");
+
+    let recv =
+        task::spawn(async move { receive_ws(QualityOfService::QoS0, "test/ws/simple").await });
+    let publ =
+        task::spawn(async move { publish_ws(5, QualityOfService::QoS0, "test/ws/simple").await });
+
+    let (r, p) = join(recv, publ).await;
+    assert_ok!(r.unwrap());
+    assert_ok!(p.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn integration_websocket_publish_recv_qos() {
+    setup();
+    info!("Running WebSocket test with Quality of Service 1");
+
+    let recv = task::spawn(async move { receive_ws(QualityOfService::QoS1, "test/ws/qos").await });
+    let publ =
+        task::spawn(async move { publish_ws(5, QualityOfService::QoS1, "test/ws/qos").await });
+
+    let (r, p) = join(recv, publ).await;
+    assert_ok!(r.unwrap());
+    assert_ok!(p.unwrap());
+}